@@ -1,5 +1,12 @@
 use relm::{Widget};
 use crate::win::*;
+use crate::model::ZoneSetEdit;
+use crate::config::ConfigOptions;
+use crate::tzselector::RowId;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::path::PathBuf;
+use timezoners_core::{CopyFormat, WorkHours, ZoneEntry, ZoneSort};
 
 #[derive(Debug, Clone)]
 pub enum MsgUpdateType {
@@ -10,26 +17,58 @@ pub enum MsgUpdateType {
 pub enum Msg {
     ProcessUpdateMsg((MsgUpdateType, String)),
     Quit,
-    AddTzSelector(String),
+    AddTzSelector(Option<ZoneEntry>),
+    UndoRemove,
     SelectDate,
     SelectColour,
     DateOkay,
     DateCancel,
     ColourOkay,
     ColourCancel,
+    FindMeeting,
+    MeetingSearch,
+    MeetingSelected(i32),
+    MeetingClose,
+    SortRows(ZoneSort),
+    ZoneSetSelected(Option<String>),
+    EditZoneSet(ZoneSetEdit),
+    ZoneSetNameOkay,
+    ZoneSetNameCancel,
+    DeleteZoneSet,
+    DeleteZoneSetResponse(bool),
+    ImportZoneSets,
+    ImportZoneSetsResponse(bool),
+    ExportZoneSet,
+    ExportZoneSetResponse(bool),
+    SaveConfig(u32),
+    SaveErrorResponse(bool),
     //Messages from child widgets
-    TimezoneSelectChanged(i32, String),
+    TimezoneSelectChanged(RowId, ZoneEntry),
     TimeSelectChanged(f64),
-    TimezoneRemove(i32),
+    DateTimeSelected(DateTime<Utc>),
+    TimezoneRemove(RowId),
+    WorkHoursChanged(RowId, WorkHours),
+    CalendarChanged(RowId, Option<PathBuf>),
+    LabelChanged(RowId, Option<String>, bool),
+    RowMoved(RowId, RowId),
     //Messages to child widgets
-    ChangeBaseTimezone(Option<String>),
+    ChangeBaseTimezone(Option<Tz>),
     SetToNow,
+    LiveToggled(bool),
+    PasteTime,
+    CopyTime(CopyFormat),
+    ExportInvite,
+    ExportInviteResponse(bool),
+    ZoneChoiceOkay,
+    ZoneChoiceCancel,
+    ClockTick,
+    DrawShared,
 }
 
 pub struct App;
 
 impl App {
-    pub fn new() {
-        Win::run(()).unwrap();
+    pub fn new(config_options: ConfigOptions) {
+        Win::run(config_options).unwrap();
     }
 }
\ No newline at end of file
@@ -0,0 +1,181 @@
+use std::cmp::Ordering;
+use chrono::{NaiveDate, Local};
+use chrono_tz::Tz;
+use timezoners_core::{current_timezone_range, slot_times, SLOT_SIZES};
+use crate::config::{ConfigOptions, load_config};
+use crate::model::Config;
+
+const USAGE: &str = "usage: timezoners grid [--zone <timezone>]... [--set <name>] [--date <yyyy-mm-dd>] [--step <minutes>]
+
+Prints the local time in each zone across the day of the first (base) zone.
+Without --zone the zones saved by the desktop app are used, from the zone set
+named by --set or otherwise the set last shown in the app. --config and
+--profile pick the settings the same way as for the desktop app.
+--step is one of 1, 5, 10, 15, 30 or 60 minutes, defaulting to 60.";
+
+struct GridArgs {
+    zones: Vec<String>,
+    zone_set: Option<String>,
+    for_date: NaiveDate,
+    step_minutes: u32,
+}
+
+// Entry point for `timezoners grid ...`, does not touch Gtk so works without a display
+pub fn run(args: &[String], config_options: &ConfigOptions) -> Result<(), String> {
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let grid_args = parse_args(args)?;
+
+    let mut zones: Vec<Tz> = Vec::new();
+    if !grid_args.zones.is_empty() {
+        for zone_name in grid_args.zones.iter() {
+            zones.push(zone_name.parse().map_err(|_| format!("unknown timezone '{}'", zone_name))?);
+        }
+    } else {
+        let mut config = match config_options.config_path() {
+            Some(path) => {
+                let (config, config_problem, _) = load_config(&path);
+                if let Some(config_problem) = config_problem {
+                    eprintln!("timezoners: {}", config_problem);
+                }
+                config
+            },
+            None => Config::default(),
+        };
+        if let Some(set_name) = grid_args.zone_set {
+            if !config.zone_sets.iter().any(|set| set.name == set_name) {
+                return Err(format!("unknown zone set '{}'", set_name));
+            }
+            config.active_set = set_name;
+        }
+        let (zone_entries, zone_warnings) = config.zone_entries();
+        for warning in zone_warnings.iter() {
+            eprintln!("timezoners: skipping saved zone: {}", warning);
+        }
+        zones.extend(zone_entries.iter().map(|entry| entry.tz));
+    }
+
+    if zones.is_empty() {
+        return Err(format!("no timezones configured, pass at least one --zone\n\n{}", USAGE));
+    }
+
+    print!("{}", render_grid(&zones, grid_args.for_date, grid_args.step_minutes));
+    Ok(())
+}
+
+fn parse_args(args: &[String]) -> Result<GridArgs, String> {
+    let mut grid_args = GridArgs {
+        zones: vec![],
+        zone_set: None,
+        for_date: Local::now().date_naive(),
+        step_minutes: 60,
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().cloned().ok_or_else(|| format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--zone" | "-z" => {
+                grid_args.zones.push(value()?);
+            },
+            "--set" => {
+                grid_args.zone_set = Some(value()?);
+            },
+            "--date" | "-d" => {
+                let date_str = value()?;
+                grid_args.for_date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+                    .map_err(|_| format!("invalid date '{}', expected yyyy-mm-dd", date_str))?;
+            },
+            "--step" | "-s" => {
+                let step_str = value()?;
+                grid_args.step_minutes = match step_str.parse::<u32>() {
+                    Ok(step) if SLOT_SIZES.contains(&step) => step,
+                    _ => return Err(format!("invalid step '{}', expected one of {:?} minutes", step_str, SLOT_SIZES)),
+                };
+            },
+            _ => {
+                return Err(format!("unknown argument '{}'\n\n{}", arg, USAGE));
+            },
+        }
+    }
+
+    Ok(grid_args)
+}
+
+// One line per zone, the first zone is the base the others are aligned to.
+// Times on the day before the base date are marked with '-' and the day after with '+'
+fn render_grid(zones: &[Tz], for_date: NaiveDate, step_minutes: u32) -> String {
+    let base_tz = zones[0];
+    let name_width = zones.iter().map(|zone| zone.name().len()).max().unwrap_or(0);
+
+    let mut output = format!("{} in {}\n", for_date.format("%Y/%m/%d"), base_tz.name());
+    for this_tz in zones.iter() {
+        let zone_day = current_timezone_range(base_tz, *this_tz, for_date);
+        output.push_str(&format!("{:width$}", this_tz.name(), width = name_width));
+        for slot in slot_times(&zone_day, step_minutes) {
+            let marker = match slot.naive_local().date().cmp(&for_date) {
+                Ordering::Less => '-',
+                Ordering::Equal => ' ',
+                Ordering::Greater => '+',
+            };
+            output.push_str(&format!(" {}{}", marker, slot.format("%H:%M")));
+        }
+        output.push('\n');
+    }
+    output.push_str("(- previous day, + next day)\n");
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America, Europe};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn bad_arguments_are_reported() {
+        let config_options = ConfigOptions::default();
+        assert_eq!(run(&args(&["--zone", "Bad/Zone"]), &config_options), Err("unknown timezone 'Bad/Zone'".to_string()));
+
+        let err = |grid_args: &[&str]| parse_args(&args(grid_args)).err().unwrap();
+        assert!(err(&["--step", "7"]).starts_with("invalid step '7'"));
+        assert!(err(&["--step", "often"]).starts_with("invalid step 'often'"));
+        assert_eq!(err(&["--zone"]), "missing value for --zone");
+        assert_eq!(err(&["--date"]), "missing value for --date");
+        assert!(err(&["--date", "14/01/2021"]).starts_with("invalid date"));
+        assert!(err(&["--zones", "UTC"]).starts_with("unknown argument '--zones'"));
+
+        let grid_args = parse_args(&args(&["-z", "UTC", "--set", "Team", "-d", "2021-01-14", "-s", "15"])).unwrap();
+        assert_eq!(grid_args.zones, vec!["UTC"]);
+        assert_eq!(grid_args.zone_set.as_deref(), Some("Team"));
+        assert_eq!(grid_args.for_date, NaiveDate::from_ymd_opt(2021, 1, 14).unwrap());
+        assert_eq!(grid_args.step_minutes, 15);
+    }
+
+    #[test]
+    fn grid_lines_up_and_marks_other_days() {
+        let for_date = NaiveDate::from_ymd_opt(2021, 1, 14).unwrap();
+        let grid = render_grid(&[Europe::London, America::New_York], for_date, 60);
+        let lines: Vec<&str> = grid.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "2021/01/14 in Europe/London");
+        assert!(lines[1].starts_with("Europe/London     00:00  01:00"));
+        assert!(lines[2].starts_with("America/New_York -19:00 -20:00"));
+        assert!(lines[2].contains(" -23:00  00:00 "));
+        assert!(lines[2].ends_with(" 18:00"));
+        assert_eq!(lines[1].len(), lines[2].len());
+        assert_eq!(lines[3], "(- previous day, + next day)");
+
+        // Ahead of the base the end of the day runs into the next
+        let grid = render_grid(&[America::New_York, Europe::London], for_date, 60);
+        assert!(grid.lines().nth(2).unwrap().ends_with(" +04:00"));
+    }
+}
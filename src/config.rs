@@ -1,15 +1,317 @@
-use serde::{Serialize, Deserialize};
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use chrono::Local;
+use directories::ProjectDirs;
+use toml::value::{Table, Value};
+use timezoners_core::DEFAULT_SET_NAME;
+use crate::model::Config;
 
-#[derive(Serialize, Deserialize)]
-pub struct Config {
-    name: String,
+pub const APP_NAME: &str = "TimezoneRS";
+
+// Bump when a change to Config needs existing files rewritten, and add a step to MIGRATIONS
+pub const CONFIG_VERSION: i64 = 1;
+
+// Step n takes the settings in a file from version n to n + 1, files written before there was a
+// version field are version 0
+const MIGRATIONS: [fn(&mut Table); CONFIG_VERSION as usize] = [
+    zone_sets_from_zones,
+];
+
+// Where the settings are kept, given on the command line before anything else
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigOptions {
+    // A settings file to use instead of the usual one, such as a team file checked into a repo
+    pub path: Option<PathBuf>,
+    // Settings kept next to the usual ones under a name, so two windows can run side by side
+    pub profile: Option<String>,
+    // Settings are read but never written back
+    pub read_only: bool,
+}
+
+impl ConfigOptions {
+    // Takes --config, --profile and --read-only out of args wherever they are, returning the
+    // options along with the arguments that are left
+    pub fn from_args(args: &[String]) -> Result<(Self, Vec<String>), String> {
+        let mut config_options = ConfigOptions::default();
+        let mut other_args = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || iter.next().cloned().ok_or_else(|| format!("missing value for {}", arg));
+            match arg.as_str() {
+                "--config" | "-c" => {
+                    config_options.path = Some(PathBuf::from(value()?));
+                },
+                "--profile" | "-p" => {
+                    let profile = value()?;
+                    if profile.is_empty() || !profile.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+                        return Err(format!("invalid profile '{}', use letters, digits, '-' and '_'", profile));
+                    }
+                    config_options.profile = Some(profile);
+                },
+                "--read-only" => {
+                    config_options.read_only = true;
+                },
+                _ => other_args.push(arg.clone()),
+            }
+        }
+
+        if config_options.path.is_some() && config_options.profile.is_some() {
+            return Err("--config and --profile cannot be used together".to_string());
+        }
+        Ok((config_options, other_args))
+    }
+
+    // A file given with --config has to be there already, so that a mistyped path is not quietly
+    // taken as new settings for the first save to write out. Profiles start from the defaults
+    pub fn check_path(&self) -> Result<(), String> {
+        match self.path.as_ref() {
+            Some(path) if !path.is_file() => Err(format!("settings file {} not found", path.display())),
+            _ => Ok(()),
+        }
+    }
+
+    // The settings file these options point at, None when there is no folder to keep settings in
+    pub fn config_path(&self) -> Option<PathBuf> {
+        match (self.path.as_ref(), self.profile.as_ref()) {
+            (Some(path), _) => Some(path.clone()),
+            (None, Some(profile)) => config_dir_path(&format!("{}-{}.toml", APP_NAME, profile)),
+            (None, None) => default_config_path(),
+        }
+    }
+
+    // Shown under the window title, so windows with different settings can be told apart
+    pub fn description(&self) -> Option<String> {
+        let source = match (self.path.as_ref(), self.profile.as_ref()) {
+            (Some(path), _) => Some(path.file_name().unwrap_or_default().to_string_lossy().to_string()),
+            (None, Some(profile)) => Some(format!("{} profile", profile)),
+            (None, None) => None,
+        };
+        match (source, self.read_only) {
+            (Some(source), true) => Some(format!("{} (read only)", source)),
+            (Some(source), false) => Some(source),
+            (None, true) => Some("Read only".to_string()),
+            (None, false) => None,
+        }
+    }
+}
+
+// Where confy used to keep the settings, ~/Library/Preferences/rs.TimezoneRS/TimezoneRS.toml on a Mac
+pub fn default_config_path() -> Option<PathBuf> {
+    config_dir_path(&format!("{}.toml", APP_NAME))
+}
+
+fn config_dir_path(file_name: &str) -> Option<PathBuf> {
+    ProjectDirs::from("rs", "", APP_NAME).map(|dirs| dirs.config_dir().join(file_name))
+}
+
+// The settings in the file at path, or the defaults along with a description of the problem if
+// the file could not be read, and whether the file may be saved over. A file that cannot be
+// understood is copied aside first so that saving the defaults over it does not lose the user's
+// zones. When that copy fails, or the file could not be read at all, it is the only copy there
+// is and must be left alone until the user has sorted it out
+pub fn load_config(path: &Path) -> (Config, Option<String>, bool) {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        // Nothing saved yet, a missing --config file is turned away by ConfigOptions::check_path
+        Err(e) if e.kind() == ErrorKind::NotFound => return (Config::default(), None, true),
+        Err(e) => return (Config::default(), Some(format!("Could not read settings from {} ({}), the defaults are in use and changes will not be saved",
+            path.display(), e)), false),
+    };
+
+    let err = match toml::from_str(&text).map_err(|e| e.to_string()).and_then(config_from_value) {
+        Ok(config) => return (config, None, true),
+        Err(err) => err,
+    };
+    let backup_path = backup_path(path);
+    match std::fs::copy(path, &backup_path) {
+        Ok(_) => (Config::default(), Some(format!("Settings in {} could not be read ({}), they were copied to {} and the defaults are in use",
+            path.display(), err, backup_path.display())), true),
+        Err(e) => (Config::default(), Some(format!("Settings in {} could not be read ({}) or copied aside ({}), the defaults are in use and changes will not be saved",
+            path.display(), err, e)), false),
+    }
+}
+
+// Writes the settings to a file next to path and then renames it over path, so a crash part way
+// through leaves the previous settings in place
+pub fn save_config(path: &Path, config: &Config) -> Result<(), String> {
+    let text = toml::to_string(config).map_err(|e| format!("Could not save settings: {}", e))?;
+    let mut temp_file_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_file_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_file_name);
+
+    let write = || -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = File::create(&temp_path)?;
+        file.write_all(text.as_bytes())?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)
+    };
+    write().map_err(|e| {
+        let _ = std::fs::remove_file(&temp_path);
+        format!("Could not save settings to {}: {}", path.display(), e)
+    })
 }
 
-//  If the content of this structure changes then delete config file from ~/Library/Preferences/<app-name> toml file
-impl Default for Config {
-    fn default() -> Self { 
-        Self { 
-            name: String::from("gtk-base"),
+// Brings settings of any earlier version up to date and reads them
+pub fn config_from_value(mut value: Value) -> Result<Config, String> {
+    let table = value.as_table_mut().ok_or("settings are not a table")?;
+    let version = match table.get("version") {
+        None => 0,
+        Some(Value::Integer(version)) if (0..=CONFIG_VERSION).contains(version) => *version,
+        Some(Value::Integer(version)) if *version > CONFIG_VERSION => {
+            return Err(format!("they were saved by a newer version of timezoners (settings version {})", version));
+        },
+        Some(version) => return Err(format!("unknown settings version {}", version)),
+    };
+
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        migration(table);
+    }
+    table.insert("version".to_string(), Value::Integer(CONFIG_VERSION));
+
+    let mut config: Config = value.try_into().map_err(|e| e.to_string())?;
+    config.ensure_active_set();
+    Ok(config)
+}
+
+// Next to the original, such as TimezoneRS.toml.20260301-093000.bak
+fn backup_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}.bak", Local::now().format("%Y%m%d-%H%M%S")));
+    path.with_file_name(file_name)
+}
+
+// Version 1 keeps zones in named sets. Before that there was a single list of zones, given as
+// plain names or as tables, with the working hours of plain names kept in a list of their own
+fn zone_sets_from_zones(table: &mut Table) {
+    let zones = table.remove("zones");
+    let work_hours = match table.remove("work_hours") {
+        Some(Value::Array(work_hours)) => work_hours,
+        _ => vec![],
+    };
+    if table.contains_key("zone_sets") {
+        return;
+    }
+
+    let zones: Vec<Value> = match zones {
+        Some(Value::Array(zones)) => zones.into_iter().enumerate()
+            .map(|(i, zone)| match (zone, work_hours.get(i)) {
+                (Value::String(zone), Some(work_hours)) => {
+                    let mut entry = Table::new();
+                    entry.insert("zone".to_string(), Value::String(zone));
+                    entry.insert("work_hours".to_string(), work_hours.clone());
+                    Value::Table(entry)
+                },
+                (zone, _) => zone,
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    let mut zone_set = Table::new();
+    zone_set.insert("name".to_string(), Value::String(DEFAULT_SET_NAME.to_string()));
+    zone_set.insert("zones".to_string(), Value::Array(zones));
+    table.insert("zone_sets".to_string(), Value::Array(vec![Value::Table(zone_set)]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe;
+
+    #[test]
+    fn unversioned_zones_move_into_a_set() {
+        let value: Value = toml::from_str("
+            win_pos_x = 5
+            zones = ['Europe/Berlin', 'Bad/Zone']
+
+            [[work_hours]]
+            start = '07:00:00'
+            end = '15:00:00'
+            weekdays = ['Mon']
+        ").unwrap();
+
+        let config = config_from_value(value).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.win_pos_x, 5);
+        assert_eq!(config.active_set, DEFAULT_SET_NAME);
+        let (entries, warnings) = config.zone_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tz, Europe::Berlin);
+        assert_eq!(entries[0].work_hours.start.to_string(), "07:00:00");
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn saved_settings_load_back() {
+        let path = std::env::temp_dir().join(format!("timezoners-{}", std::process::id())).join("TimezoneRS.toml");
+        let mut config = Config { win_width: 640, ..Config::default() };
+        config.zone_sets[0].zones.push(timezoners_core::SavedZone::Name("Europe/Berlin".to_string()));
+
+        save_config(&path, &config).unwrap();
+        let (loaded, problem, can_save) = load_config(&path);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(problem, None);
+        assert!(can_save);
+        assert_eq!(loaded.win_width, 640);
+        assert_eq!(loaded.zone_entries().0[0].tz, Europe::Berlin);
+    }
+
+    #[test]
+    fn unreadable_settings_are_not_saved_over() {
+        let dir = std::env::temp_dir().join(format!("timezoners-unreadable-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Not valid UTF-8, so it cannot be read as text
+        let path = dir.join("binary.toml");
+        std::fs::write(&path, [0xff, 0xfe, 0x00]).unwrap();
+        let (_, problem, can_save) = load_config(&path);
+        assert!(problem.is_some());
+        assert!(!can_save);
+
+        // Copied aside, so the defaults can be saved over it
+        let path = dir.join("garbled.toml");
+        std::fs::write(&path, "zone_sets = 5").unwrap();
+        let (_, problem, can_save) = load_config(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(problem.is_some());
+        assert!(can_save);
+    }
+
+    #[test]
+    fn config_options_are_taken_from_args() {
+        let args: Vec<String> = ["grid", "--profile", "work", "--zone", "UTC", "--read-only"].iter().map(|arg| arg.to_string()).collect();
+        let (config_options, other_args) = ConfigOptions::from_args(&args).unwrap();
+        assert_eq!(config_options.profile.as_deref(), Some("work"));
+        assert!(config_options.read_only);
+        assert_eq!(other_args, vec!["grid", "--zone", "UTC"]);
+        if let Some(path) = config_options.config_path() {
+            assert!(path.ends_with("TimezoneRS-work.toml"));
         }
+
+        let args: Vec<String> = vec!["--config".to_string(), "./team-zones.toml".to_string()];
+        let (config_options, _) = ConfigOptions::from_args(&args).unwrap();
+        assert_eq!(config_options.config_path(), Some(PathBuf::from("./team-zones.toml")));
+        assert!(config_options.check_path().is_err());
+        assert!(ConfigOptions::default().check_path().is_ok());
+
+        let args: Vec<String> = ["--config", "a.toml", "--profile", "work"].iter().map(|arg| arg.to_string()).collect();
+        assert!(ConfigOptions::from_args(&args).is_err());
+        let args: Vec<String> = vec!["--profile".to_string(), "../work".to_string()];
+        assert!(ConfigOptions::from_args(&args).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn newer_settings_are_not_read() {
+        let value: Value = toml::from_str(&format!("version = {}", CONFIG_VERSION + 1)).unwrap();
+        assert!(config_from_value(value).is_err());
+
+        let value: Value = toml::from_str("version = 'one'").unwrap();
+        assert!(config_from_value(value).is_err());
+    }
+}
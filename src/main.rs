@@ -8,16 +8,58 @@ extern crate relm_test;
 extern crate chrono;
 extern crate chrono_tz;
 extern crate gdk;
+extern crate timezoners_core;
 
 use crate::app::App;
+use crate::config::ConfigOptions;
 
 mod app;
+mod cli;
 mod config;
 mod win;
 mod model;
 mod widgets;
 mod tzselector;
+mod zone_file;
+
+const USAGE: &str = "usage: timezoners [--config <file> | --profile <name>] [--read-only] [grid ...]
+
+--config uses the settings in <file> instead of the usual ones, such as a team
+file checked into a repo. --profile keeps settings under <name> alongside the
+usual ones, so windows with different profiles can run side by side. With
+--read-only the settings are never written. `timezoners grid --help` describes
+printing the zones without a display.";
 
 fn main() {
-    App::new();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (config_options, args) = match ConfigOptions::from_args(&args) {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            eprintln!("timezoners: {}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        },
+    };
+    if let Err(err) = config_options.check_path() {
+        eprintln!("timezoners: {}", err);
+        std::process::exit(1);
+    }
+
+    // Headless mode, prints the aligned timezones without starting Gtk
+    if args.first().map(String::as_str) == Some("grid") {
+        if let Err(err) = cli::run(&args[1..], &config_options) {
+            eprintln!("timezoners: {}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    match args.first().map(String::as_str) {
+        None => App::new(config_options),
+        Some("--help") | Some("-h") => println!("{}", USAGE),
+        Some(arg) => {
+            eprintln!("timezoners: unknown argument '{}'\n\n{}", arg, USAGE);
+            std::process::exit(2);
+        },
+    }
 }
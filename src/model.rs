@@ -1,42 +1,129 @@
 // use gtk::{ListStore, TreeModelFilter};
-use relm::{Sender, Component, Relm};
+use relm::{Sender, Component, Relm, DrawHandler};
+use gtk::DrawingArea;
 use chrono::{NaiveDate};
+use chrono_tz::Tz;
 use crate::tzselector::*;
 use serde::{Serialize, Deserialize};
-use crate::{win::Win, app::MsgUpdateType};
+use timezoners_core::{DEFAULT_SET_NAME, DEFAULT_SLOT_MINUTES, MeetingWindow, TimeEntry, ZoneEntry, ZoneSet};
+use crate::{win::Win, app::MsgUpdateType, config::{CONFIG_VERSION, ConfigOptions}};
 
 // #[derive(Debug)]
 pub struct Model {
     pub tz_ctrls: Vec<Component<TzSelector>>,
-    pub tz_zones: Vec<Option<String>>,
+    // One per entry in tz_ctrls, None until a timezone is chosen
+    pub tz_zones: Vec<Option<ZoneEntry>>,
+    // One per entry in tz_ctrls, what each row calls itself in its messages
+    pub tz_row_ids: Vec<RowId>,
+    pub next_row_id: RowId,
+    // Position and settings of the last row removed, so it can be put back
+    pub removed_row: Option<(usize, Option<ZoneEntry>)>,
     pub sender: Sender<(MsgUpdateType, String)>, 
     pub local_relm: Relm<Win>,
-    pub base_tz: Option<String>,
+    pub base_tz: Option<Tz>,
     pub for_date: NaiveDate,
+    // Parts of the day when every row is working, drawn once across all the rows
+    pub shared: Vec<(f64, f64)>,
+    pub shared_draw_handler: DrawHandler<DrawingArea>,
+    // Base timezone slot the sliders are at
+    pub selected_slot: f64,
+    pub meeting_windows: Vec<MeetingWindow>,
+    // What the set name dialog is open for
+    pub set_name_edit: ZoneSetEdit,
+    // Bumped for each change, a queued save only goes ahead if no change has come in since
+    pub save_generation: u32,
+    // Following the clock
+    pub live: bool,
+    // Base timezone slot the sliders were last moved to by live mode
+    pub live_slot: Option<i64>,
+    // A pasted time waiting for the user to say which timezone its abbreviation means
+    pub pending_paste: Option<TimeEntry>,
+    // Where settings are loaded from and saved to, from the command line
+    pub config_options: ConfigOptions,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZoneSetEdit {
+    Create,
+    Rename,
+    Duplicate,
+}
+
+const DEFAULT_DST_WARNING_WEEKS: u32 = 4;
+
+// Anything missing from the file is taken from Config::default(), so a file holding only zone
+// sets can be read as a config as well. Files saved by earlier versions are brought up to date
+// by the migrations in config.rs
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
-    pub zones:            Vec<Option<String>>,
+    pub version:          i64,
     pub win_pos_x:        i32,
     pub win_pos_y:        i32,
     pub win_width:        i32,
     pub win_height:       i32,
     pub midday_colour:    (f64, f64, f64, f64),
     pub workday_colour:   (f64, f64, f64, f64),
+    // Minutes per slider step, one of timezoners_core::SLOT_SIZES
+    #[serde(default = "default_slot_minutes")]
+    pub slot_minutes:     u32,
+    // How far ahead rows warn of their clocks changing, 0 for no warnings
+    #[serde(default = "default_dst_warning_weeks")]
+    pub dst_warning_weeks: u32,
+    // Name of the zone set shown when the app was last closed
+    #[serde(default)]
+    pub active_set:       String,
+    // Tables from here on as toml needs them after plain values
+    #[serde(default)]
+    pub zone_sets:        Vec<ZoneSet>,
+}
+
+impl Config {
+    // Makes sure there is always a set to show
+    pub fn ensure_active_set(&mut self) {
+        if self.zone_sets.is_empty() {
+            self.zone_sets.push(ZoneSet::new(DEFAULT_SET_NAME));
+        }
+        if self.active_set_index().is_none() {
+            self.active_set = self.zone_sets[0].name.clone();
+        }
+    }
+
+    pub fn active_set_index(&self) -> Option<usize> {
+        self.zone_sets.iter().position(|set| set.name == self.active_set)
+    }
+
+    // The zones of the active set that could be read, plus a warning for each one that could not
+    pub fn zone_entries(&self) -> (Vec<ZoneEntry>, Vec<String>) {
+        match self.active_set_index() {
+            Some(index) => self.zone_sets[index].entries(),
+            None => (vec![], vec![]),
+        }
+    }
+}
+
+fn default_slot_minutes() -> u32 {
+    DEFAULT_SLOT_MINUTES
+}
+
+fn default_dst_warning_weeks() -> u32 {
+    DEFAULT_DST_WARNING_WEEKS
 }
 
-//  If the content of this structure changes then delete config file from ~/Library/Preferences/<app-name> toml file
 impl Default for Config {
     fn default() -> Self { 
         Self { 
-            zones:            Vec::new(),
+            version:          CONFIG_VERSION,
             win_pos_x:        0,
             win_pos_y:        0,
             win_width:        500,
             win_height:       300,
             midday_colour:    (0.98, 0.86, 0.12, 0.5),
             workday_colour:   (0.2, 0.2, 0.9, 0.9),
+            slot_minutes:     DEFAULT_SLOT_MINUTES,
+            dst_warning_weeks: DEFAULT_DST_WARNING_WEEKS,
+            active_set:       String::from(DEFAULT_SET_NAME),
+            zone_sets:        vec![ZoneSet::new(DEFAULT_SET_NAME)],
         }
     }
 }
\ No newline at end of file
@@ -1,42 +1,92 @@
 use glib::{ToValue, Type};
-use gtk::{Box, Button, ButtonExt, CellRendererExt, ComboBox, ComboBoxExt, EntryCompletionExt, Inhibit, Label, LabelExt, RangeExt, Scale, TreeModelExt, WidgetExt};
-use gtk::{Builder, prelude::{GtkListStoreExtManual, BuilderExtManual}, Adjustment, DrawingArea,
+use gdk::{DragAction, ModifierType};
+use gtk::{AdjustmentExt, Box, Button, ButtonExt, CellRendererExt, CheckButton, ComboBox, ComboBoxExt, DestDefaults, Dialog, DialogExt, EntryCompletionExt, EventBox, FileChooserDialog, FileChooserExt, Inhibit, Label, LabelExt, RangeExt, ResponseType, Scale, TargetEntry, TargetFlags, ToggleButtonExt, TreeModelExt, WidgetExt};
+use gtk::{Builder, prelude::{GtkListStoreExtManual, BuilderExtManual, WidgetExtManual}, Adjustment, DrawingArea,
             EntryExt, ListStore, TreeModelFilter, GtkListStoreExt, TreeViewColumnBuilder, CellRendererTextBuilder, 
-            CellLayoutExt, TreeModel, TreeIter, CssProvider, CssProviderExt, STYLE_PROVIDER_PRIORITY_APPLICATION, StyleContextExt, Entry, EntryCompletion,};
+            CellLayoutExt, TreeModel, TreeIter, CssProvider, CssProviderExt, STYLE_PROVIDER_PRIORITY_APPLICATION, StyleContextExt, Entry, EntryCompletion, EntryIconPosition,};
 use relm::{Update, Widget, Relm, DrawHandler};
 use cairo::{LinearGradient, Matrix,};
-use chrono::{TimeZone, NaiveDate, NaiveTime, Local, Datelike, Timelike, Duration, DateTime};
+use chrono::{NaiveDate, NaiveTime, Local, DateTime, Utc, Weekday};
 use chrono_tz::{TZ_VARIANTS, Tz};
+use std::path::PathBuf;
+use timezoners_core::{CalendarEvent, busy_day_spans, events_from_ics, Colour, WorkHours, ZoneDay, ZoneEntry, label_initials, current_timezone_range, time_string_from_index, index_from_time, day_percent_complete, format_minutes, upcoming_offset_warnings, parse_time_entry, ZoneInfo};
 
 use self::Msg::*;
 
+// Identifies a row for as long as it exists, whatever position it is moved to
+pub type RowId = u32;
+
+// Drag and drop target for moving a row, the data is the id of the row dragged
+const ROW_DRAG_TARGET: &str = "timezoners/row";
+
+// Order of the working day check buttons
+const WEEKDAYS: [Weekday; 7] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun];
+
 #[derive(Clone, Msg)]
 pub enum Msg {
     SearchMatchSelected(TreeModel, TreeIter),
     DrawIllumination,
     RemoveTz,
     LocalTimezoneSelect,
-    NotifyParentTimezoneSelectChanged(i32, String),
+    NotifyParentTimezoneSelectChanged(RowId, ZoneEntry),
     LocalTimeSelect(f64),
     TimeEntryChanged,
+    TimeEntryEdited,
+    NotifyParentDateTimeSelected(DateTime<Utc>),
     NotifyParentTimeSelectChanged(f64),
-    NotifyParentBaseTzChanged(String),
-    NotifyParentTzSelectorRemoveClicked(i32),
+    NotifyParentBaseTzChanged(Tz),
+    NotifyParentTzSelectorRemoveClicked(RowId),
     FromParentBaseTimeSelectChanged(f64),
-    FromParentBaseTimezoneChanged(Option<String>),
+    FromParentBaseTimezoneChanged(Option<Tz>),
     FromParentDateChanged(NaiveDate),
     FromParentColourChanged((f64, f64, f64, f64), (f64, f64, f64, f64)),
     FromParentSetToNow,
+    FromParentBlockingChanged(Vec<(f64, f64)>),
+    EditWorkHours,
+    WorkHoursOkay,
+    WorkHoursCancel,
+    NotifyParentWorkHoursChanged(RowId, WorkHours),
+    EditLabel,
+    LabelOkay,
+    LabelCancel,
+    NotifyParentLabelChanged(RowId, Option<String>, bool),
+    FromParentLabelsChanged(Vec<(Tz, String)>),
+    RowDropped(Option<RowId>),
+    NotifyParentRowMoved(RowId, RowId),
+    FromParentBaseChanged(bool),
+    FromParentClockTick,
+    EditCalendar,
+    CalendarResponse(ResponseType),
+    NotifyParentCalendarChanged(RowId, Option<PathBuf>),
 }
 pub struct TzSelectorModel {
-    index: i32,
+    row_id: RowId,
+    // The first row is the base timezone
+    is_base: bool,
     for_date: NaiveDate,
-    base_timezone: Option<String>,
-    this_timezone: Option<String>,
+    base_timezone: Option<Tz>,
+    this_timezone: Option<Tz>,
+    label: Option<String>,
+    show_badge: bool,
     local_relm: Relm<TzSelector>,
     draw_handler: DrawHandler<DrawingArea>,
-    midday_colour: (f64, f64, f64, f64),
-    workday_colour: (f64, f64, f64, f64),
+    midday_colour: Colour,
+    workday_colour: Colour,
+    // Colours set for this row only, take the place of the app wide ones above
+    own_midday_colour: Option<Colour>,
+    own_workday_colour: Option<Colour>,
+    slot_minutes: u32,
+    // Clock changes this far ahead are flagged on the row
+    dst_warning_weeks: u32,
+    // Base date the warnings were last looked for on, they only change from one day to the next
+    dst_warning_date: Option<NaiveDate>,
+    work_hours: WorkHours,
+    // Parts of the day when only this row is not working
+    blocking: Vec<(f64, f64)>,
+    // Calendar file shown on the row, the events read from it and the parts of the day they take up
+    calendar: Option<PathBuf>,
+    calendar_events: Vec<CalendarEvent>,
+    busy: Vec<(f64, f64)>,
     pub liststore: ListStore,
     pub liststorefilter: TreeModelFilter,
 }
@@ -49,11 +99,27 @@ pub struct TzSelectorWidgets {
     pub cmb_tz_name: ComboBox,
     pub tz_scale_adj: Adjustment,
     pub lbl_current_select_time: Label,
+    pub lbl_zone_info: Label,
+    pub lbl_dst_warning: Label,
     pub pb_remove_tz: Button,
     pub draw_illum: DrawingArea,
     pub cmb_tz_name_entrycompletion: EntryCompletion,
     pub cmb_tz_name_entry: Entry,
     pub txt_time_entry: Entry,
+    pub pb_work_hours: Button,
+    pub dlg_work_hours: Dialog,
+    pub txt_work_start: Entry,
+    pub txt_work_end: Entry,
+    pub chk_work_days: Vec<CheckButton>,
+    pub pb_row_label: Button,
+    pub lbl_badge: Label,
+    pub lbl_row_label: Label,
+    pub dlg_row_label: Dialog,
+    pub txt_row_label: Entry,
+    pub chk_row_badge: CheckButton,
+    pub evb_drag_handle: EventBox,
+    pub pb_calendar: Button,
+    pub dlg_calendar: FileChooserDialog,
 }
 
 
@@ -65,71 +131,199 @@ pub struct TzSelector {
 impl TzSelector {
 
     pub fn set_time_to_now(&mut self) {
-        let mut tz_curr: Tz = Tz::UTC;
-        if let Some(tz) = self.model.this_timezone.clone() {
-            if tz.len() > 0 {
-                tz_curr = tz.parse().unwrap();
-            }
+        if let Some((base_tz, this_tz)) = self.get_zones() {
+            let curr_time: DateTime<Tz> = Local::now().with_timezone(&this_tz);
+            let index = index_from_time(base_tz, this_tz, self.model.for_date, curr_time.time(), self.model.slot_minutes);
+            self.model.local_relm.stream().emit(Msg::LocalTimeSelect(index.round()));
         }
-        
-        let curr_time: DateTime<Tz> = Local::now().with_timezone(&tz_curr);
-        let index = get_index_from_time_string(self.model.this_timezone.clone(), self.model.base_timezone.clone().unwrap(), self.model.for_date,  &curr_time.format("%H:%M").to_string());
-        self.model.local_relm.stream().emit(Msg::LocalTimeSelect(index.round()));
     }
 
-    pub fn set_index(&mut self, index: i32) {
-        self.model.index = index;
+    pub fn set_base(&mut self, is_base: bool) {
+        self.model.is_base = is_base;
+        //Base timezone widget cannot be removed
+        self.widgets.pb_remove_tz.set_sensitive(!is_base);
+        self.widgets.pb_remove_tz.set_visible(!is_base);
+    }
+
+    // Base and current timezone, only if both have been selected
+    fn get_zones(&self) -> Option<(Tz, Tz)> {
+        Some((self.model.base_timezone?, self.model.this_timezone?))
+    }
+
+    // Everything set up for this row, once a timezone has been selected
+    fn get_entry(&self) -> Option<ZoneEntry> {
+        Some(ZoneEntry {
+            tz: self.model.this_timezone?,
+            label: self.model.label.clone(),
+            show_badge: self.model.show_badge,
+            midday_colour: self.model.own_midday_colour,
+            workday_colour: self.model.own_workday_colour,
+            work_hours: self.model.work_hours.clone(),
+            calendar: self.model.calendar.clone(),
+        })
+    }
+
+    fn get_colours(&self) -> (Colour, Colour) {
+        (self.model.own_midday_colour.unwrap_or(self.model.midday_colour),
+         self.model.own_workday_colour.unwrap_or(self.model.workday_colour))
+    }
+
+    fn get_zone_day(&self) -> Option<ZoneDay> {
+        let (base_tz, this_tz) = self.get_zones()?;
+        Some(current_timezone_range(base_tz, this_tz, self.model.for_date))
     }
 
     fn update_time_labels(&self) {
-        match &self.model.base_timezone {
-            Some(base_zone) => {
-                
-                //This just creates default values that will be updated in the function below (scope headaches in if blocks)
-                let mut curr_start_time_tz: DateTime<Tz> = Local.from_local_date(&self.model.for_date).earliest().unwrap().and_hms(12, 0, 0).with_timezone(&Tz::UTC);
-                let mut curr_end_time_tz: DateTime<Tz> = Local.from_local_date(&self.model.for_date).earliest().unwrap().and_hms(12, 0, 0).with_timezone(&Tz::UTC);
-
-                let (opt_curr_start_time_tz, opt_curr_end_time_tz, start_same_date, end_same_date) = get_current_timezone_range(String::from(base_zone), self.model.this_timezone.clone(), self.model.for_date);
-                
-                if opt_curr_end_time_tz == None || opt_curr_start_time_tz == None {
-                    return;
-                }
+        if let Some(zone_day) = self.get_zone_day() {
+            if zone_day.start_same_date {
+                self.widgets.lbl_start.set_text(format!("{}", zone_day.start.format("%I:%M %P")).as_ref());
+            } else {
+                self.widgets.lbl_start.set_text(format!("{}", zone_day.start.format("* %I:%M %P")).as_ref());
+            }
 
-                if let Some(start_tz) = opt_curr_start_time_tz {
-                    curr_start_time_tz = start_tz; 
-                }
+            if zone_day.end_same_date {
+                self.widgets.lbl_end.set_text(format!("{}", zone_day.end.format("%I:%M %P")).as_ref());
+            } else {
+                self.widgets.lbl_end.set_text(format!("{}", zone_day.end.format("* %I:%M %P")).as_ref());
+            }
+        }
+    }
 
-                if let Some(end_tz) = opt_curr_end_time_tz {
-                    curr_end_time_tz = end_tz;
-                }
+    //With no timezone selected yet the slider still runs midnight to midnight
+    fn get_display_day(&self) -> ZoneDay {
+        self.get_zone_day().unwrap_or_else(|| current_timezone_range(Tz::UTC, Tz::UTC, self.model.for_date))
+    }
 
-                if let Some(b) = start_same_date {
-                    if b {
-                        self.widgets.lbl_start.set_text(format!("{}", curr_start_time_tz.format("%I:%M %P")).as_ref());
-                    } else {
-                        self.widgets.lbl_start.set_text(format!("{}", curr_start_time_tz.format("* %I:%M %P")).as_ref());    
-                    }
-                }
-                    
-                if let Some(b) = end_same_date {
-                    if b {
-                        self.widgets.lbl_end.set_text(format!("{}", curr_end_time_tz.format("%I:%M %P")).as_ref());
-                    } else {
-                        self.widgets.lbl_end.set_text(format!("{}", curr_end_time_tz.format("* %I:%M %P")).as_ref());    
-                    }
-                }
-                
+    //Slider steps are one slot each, page up/down moves an hour. The base day can be longer or
+    //shorter than 24 hours when its clocks change
+    fn update_slider_range(&self) {
+        let slots = self.get_display_day().slot_count(self.model.slot_minutes) as f64;
+        self.widgets.tz_scale_adj.set_upper(slots);
+        self.widgets.tz_scale_adj.set_page_increment((60 / self.model.slot_minutes) as f64);
+        self.widgets.slider.set_fill_level(slots);
+    }
 
+    fn update_time_display(&self) {
+        let slider_value = self.widgets.slider.get_value();
+        let zone_day = self.get_display_day();
+        let display_value = time_string_from_index(slider_value.round(), &zone_day, self.model.slot_minutes);
+        self.widgets.lbl_current_select_time.set_text(&display_value);
+
+        // How this timezone stands at the selected instant, which can change with the date or time
+        let zone_info = match self.get_zones() {
+            Some((base_tz, _)) => ZoneInfo::at(zone_day.time_at_index(slider_value.round(), self.model.slot_minutes), base_tz)
+                .summary(self.model.is_base),
+            None => String::new(),
+        };
+        self.widgets.lbl_zone_info.set_text(&zone_info);
+    }
+
+    // Clock changes coming up in this timezone, from today rather than the date shown as they
+    // matter for meetings already booked
+    fn update_dst_warning(&mut self) {
+        self.model.dst_warning_date = Some(self.today());
+        let warnings: Vec<String> = match self.get_zones() {
+            Some((base_tz, this_tz)) if self.model.dst_warning_weeks > 0 => {
+                upcoming_offset_warnings(this_tz, base_tz, Utc::now(), self.model.dst_warning_weeks).iter()
+                    .map(|warning| warning.describe(self.model.is_base))
+                    .collect()
+            },
+            _ => vec![],
+        };
+        self.widgets.lbl_dst_warning.set_text(&warnings.join("\n"));
+        self.widgets.lbl_dst_warning.set_visible(!warnings.is_empty());
+    }
+
+    // Today's date in the base timezone
+    fn today(&self) -> NaiveDate {
+        match self.model.base_timezone {
+            Some(base_tz) => Utc::now().with_timezone(&base_tz).date_naive(),
+            None => Utc::now().date_naive(),
+        }
+    }
+
+    // Marks the time entry as not understood, with the reason on hover
+    fn show_time_entry_error(&self, err: Option<&str>) {
+        let style_context = self.widgets.txt_time_entry.get_style_context();
+        match err {
+            Some(err) => {
+                style_context.add_class("error");
+                self.widgets.txt_time_entry.set_icon_from_icon_name(EntryIconPosition::Secondary, Some("dialog-error-symbolic"));
+                self.widgets.txt_time_entry.set_icon_tooltip_text(EntryIconPosition::Secondary, Some(err));
             },
             None => {
+                style_context.remove_class("error");
+                self.widgets.txt_time_entry.set_icon_from_icon_name(EntryIconPosition::Secondary, None);
             },
         }
     }
 
-    fn update_time_display(&self) {
-        let slider_value = self.widgets.slider.get_value();
-        let display_value = get_time_string_from_index(slider_value.round(), self.widgets.lbl_start.get_text().as_str());
-        self.widgets.lbl_current_select_time.set_text(&display_value);
+    // Reads the events of the row's calendar file again. Times in the file without a timezone are
+    // taken to be in the row's timezone
+    fn load_calendar(&mut self) {
+        self.model.calendar_events = vec![];
+        self.model.busy = vec![];
+        let style_context = self.widgets.pb_calendar.get_style_context();
+        style_context.remove_class("error");
+        let path = match self.model.calendar.as_ref() {
+            Some(path) => path,
+            None => {
+                self.widgets.pb_calendar.set_tooltip_text(Some("Show busy times from a calendar file for this timezone"));
+                return;
+            },
+        };
+
+        let default_tz = self.model.this_timezone.unwrap_or(Tz::UTC);
+        match std::fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|text| events_from_ics(&text, default_tz)) {
+            Ok(events) => {
+                self.model.calendar_events = events;
+                self.widgets.pb_calendar.set_tooltip_text(Some(&format!("Busy times from {}", path.display())));
+            },
+            Err(err) => {
+                style_context.add_class("error");
+                self.widgets.pb_calendar.set_tooltip_text(Some(&format!("Could not read {}: {}", path.display(), err)));
+            },
+        }
+        self.update_busy();
+    }
+
+    // Worked out when the events or the day shown change rather than on every draw
+    fn update_busy(&mut self) {
+        self.model.busy = match self.get_zone_day() {
+            Some(zone_day) => busy_day_spans(&self.model.calendar_events, &zone_day),
+            None => vec![],
+        };
+    }
+
+    fn update_row_label(&self) {
+        match self.model.label.as_ref() {
+            Some(label) => {
+                let initials = label_initials(label);
+                self.widgets.lbl_row_label.set_text(label);
+                self.widgets.lbl_badge.set_text(initials.as_deref().unwrap_or(""));
+                self.widgets.lbl_badge.set_visible(self.model.show_badge && initials.is_some());
+            },
+            None => {
+                self.widgets.lbl_row_label.set_text("Add label");
+                self.widgets.lbl_badge.set_visible(false);
+            },
+        }
+    }
+
+    //Second column holds the labels of rows in that timezone so the completion finds them too
+    fn update_label_column(&self, labels: &[(Tz, String)]) {
+        if let Some(row) = self.model.liststore.get_iter_first() {
+            for tz in TZ_VARIANTS.iter() {
+                let tz_labels: Vec<&str> = labels.iter()
+                    .filter(|(label_tz, _)| label_tz == tz)
+                    .map(|(_, label)| label.as_str())
+                    .collect();
+                self.model.liststore.set_value(&row, 1, &tz_labels.join(", ").to_value());
+                if !self.model.liststore.iter_next(&row) {
+                    break;
+                }
+            }
+        }
     }
 
     fn setup_cmb_liststore(&self) {
@@ -176,50 +370,49 @@ impl TzSelector {
         self.widgets.cmb_tz_name.set_id_column(0);
     }
     
+    // Fraction of the day bar at which now falls, None unless the date shown is today in the base
+    // timezone
+    fn now_offset(&self, zone_day: &ZoneDay) -> Option<f64> {
+        let base_tz = self.model.base_timezone?;
+        let now = Utc::now();
+        if now.with_timezone(&base_tz).naive_local().date() != self.model.for_date {
+            return None;
+        }
+        let now_offset = zone_day.offset_for_instant(now.with_timezone(&zone_day.start.timezone()));
+        Some(now_offset).filter(|now_offset| (0.0..=1.0).contains(now_offset))
+    }
+
     fn draw_daytime_background(&mut self) {
         
         let ctx = self.model.draw_handler.get_context();
-        let mut curr_start_time_tz: DateTime<Tz> = Local::now().with_timezone(&Tz::UTC);
-        let mut base_tz: &str = "";
         
         let (x, y, w, h) = ctx.clip_extents();
     
-        if self.model.base_timezone == None {
-            return;
-        }
-    
-        if let Some(param_base_tz) = &self.model.base_timezone {
-            base_tz = param_base_tz.as_str();
-        }
-    
-        let (opt_curr_start_time_tz, _opt_curr_end_time_tz, _, _) = get_current_timezone_range(String::from(base_tz), self.model.this_timezone.clone(), self.model.for_date);
-                    
-        if opt_curr_start_time_tz == None {
-            return;
-        }
-    
-        if let Some(start_tz) = opt_curr_start_time_tz {
-            curr_start_time_tz = start_tz;
-        }
+        let zone_day = match self.get_zone_day() {
+            Some(zone_day) => zone_day,
+            None => return,
+        };
+        let curr_start_time_tz = zone_day.start;
+        let (midday_colour, workday_colour) = self.get_colours();
     
-        let day_start = calc_offset_for_time(curr_start_time_tz, 8, 0, 0);
-        let day_end = calc_offset_for_time(curr_start_time_tz, 17, 0, 0);
+        // Width of 24 hours, less than w on a 25 hour day
+        let w_day = w * (24.0 * 60.0) / zone_day.length_minutes as f64;
        
         // Create gradient twice the width of the output area and then copy  subset from it
-        let gr_two_day = LinearGradient::new(x, y, w*2.0, h);
+        let gr_two_day = LinearGradient::new(x, y, w_day*2.0, h);
         gr_two_day.add_color_stop_rgba(0.0, 0.2, 0.2, 0.2, 0.3);
-        gr_two_day.add_color_stop_rgba(0.25, self.model.midday_colour.0, 
-                                                     self.model.midday_colour.1, 
-                                                     self.model.midday_colour.2, 
-                                                     self.model.midday_colour.3);
+        gr_two_day.add_color_stop_rgba(0.25, midday_colour.0, 
+                                             midday_colour.1, 
+                                             midday_colour.2, 
+                                             midday_colour.3);
         gr_two_day.add_color_stop_rgba(0.5, 0.2, 0.2, 0.2, 0.5);
-        gr_two_day.add_color_stop_rgba(0.75,  self.model.midday_colour.0, 
-                                                      self.model.midday_colour.1, 
-                                                      self.model.midday_colour.2, 
-                                                      self.model.midday_colour.3);
+        gr_two_day.add_color_stop_rgba(0.75,  midday_colour.0, 
+                                              midday_colour.1, 
+                                              midday_colour.2, 
+                                              midday_colour.3);
         gr_two_day.add_color_stop_rgba(1.0, 0.2, 0.2, 0.2, 0.8);
         
-        let tx_index = calc_day_percent_complete(curr_start_time_tz);
+        let tx_index = day_percent_complete(curr_start_time_tz);
         let mut mtx = Matrix::identity();
         // Translation is the number of pixels we need to shift the source image to
         // get it into the state we want to display. Therefore shift the source left (which is analogous
@@ -227,7 +420,7 @@ impl TzSelector {
         // It is done as a percentage of the source image but because w is dest and s is 2/d we
         // dont need to divide by 2 then multiply by 2
         // translate(tx_index * ( w * 2 / 2))
-        mtx.translate(tx_index * w, 0.0);
+        mtx.translate(tx_index * w_day, 0.0);
         gr_two_day.set_matrix(mtx);
 
         ctx.set_source_rgba(1.0, 0.2, 0.2, 1.0);
@@ -236,159 +429,80 @@ impl TzSelector {
         ctx.set_source(&gr_two_day);
         ctx.paint();
 
-        ctx.set_source_rgba(self.model.workday_colour.0, 
-                            self.model.workday_colour.1, 
-                            self.model.workday_colour.2, 
-                            self.model.workday_colour.3);
-        if day_end > day_start {
-            ctx.rectangle(day_start*w, 1.0, w*(day_end-day_start), h-2.0);
-        } else {
-            ctx.rectangle(-2.0, 1.0, w*(day_end), h-2.0);
-            ctx.rectangle(day_start*w, 1.0, w, h-2.0);
+        // Events from the row's calendar, across the top half so the working hours still show
+        ctx.set_source_rgba(0.35, 0.35, 0.35, 0.55);
+        for (from, to) in self.model.busy.iter() {
+            ctx.rectangle(from * w, 1.0, (to - from) * w, h / 2.0);
         }
+        ctx.fill();
 
-        ctx.stroke();
-        
-    }
-}
-
-// Returns start and end DateTimes for the current timezone based off the start and end times of the base timezones
-//The booleans indicate if the current timezone start and end values are today or not (if false they are likely yesterday )
-fn get_current_timezone_range(base_tz: String, this_tz: Option<String>, for_date: NaiveDate) -> (Option<DateTime<Tz>>, Option<DateTime<Tz>>, Option<bool>, Option<bool>) {
-    
-    let (base_start_time_tz, base_end_time_tz) = get_base_timezone_range(base_tz.clone(), for_date);
-
-    let tz_curr: Tz;
-    if let Some(tz) = this_tz.clone() {
-        if tz.len() > 0 {
-            tz_curr = tz.parse().unwrap();
-        } else {
-            return (None, None, None, None)
+        // Faint strip where this row is all that stops everyone overlapping
+        ctx.set_source_rgba(0.9, 0.3, 0.1, 0.35);
+        for (from, to) in self.model.blocking.iter() {
+            ctx.rectangle(from * w, h - 5.0, (to - from) * w, 4.0);
         }
-    } else {
-        //If no timezone is selected for current control do nothing
-        return (None, None, None, None);
-    }
-
-    let curr_start_time_tz = base_start_time_tz.with_timezone(&tz_curr);
-    let curr_end_time_tz = base_end_time_tz.with_timezone(&tz_curr);
-    let b_start: bool = if curr_start_time_tz.date() < base_start_time_tz.date() {
-        false
-    } else {
-        true
-    };
-
-    let b_end: bool = if curr_end_time_tz.date() > base_end_time_tz.date() {
-        false
-    } else {
-        true
-    };
-
-
-    return(Some(curr_start_time_tz), Some(curr_end_time_tz), Some(b_start), Some(b_end));
-}
-
-//Time range of the 'root' or main timezone entry
-fn get_base_timezone_range(base_tz: String, for_date: NaiveDate) -> (DateTime<Tz>, DateTime<Tz>) {
-    
-    let tz_base: Tz = base_tz.parse().unwrap();
-    let base_start_time_tz = tz_base.ymd(for_date.year(), for_date.month(), for_date.day()).and_hms(0, 0, 0);
-    let base_end_time_tz = tz_base.ymd(for_date.year(), for_date.month(), for_date.day()).and_hms(23, 59, 59);
-    
-    return (base_start_time_tz, base_end_time_tz);
-}
-
-//From the slider index (0-96) calculate the time string for the current slider position
-fn get_time_string_from_index(value: f64, start_time: &str) ->String {
-    let (starting_time, prev_day) = if start_time.contains("*") {
-        (NaiveTime::parse_from_str(&start_time[2..], "%I:%M %P").unwrap(), true)
-    } else {
-        (NaiveTime::parse_from_str(start_time, "%I:%M %P").unwrap(), false)
-    };
-    
-    let offset_dur: Duration = Duration::minutes(value as i64 * 15);
-    let calc_time: NaiveTime = starting_time + offset_dur;
-    
-    let check_still_prevday = (starting_time.hour() as i64 - 24) as i64 + offset_dur.num_hours();
-    let check_next_day = (starting_time.hour() as i64 - 24) as i64 + offset_dur.num_hours();
-    let ret_string = if prev_day && check_still_prevday < 0 {
-        String::from(format!("{}", calc_time.format("<= %I:%M %P")))
-    } else {
-        if !prev_day && check_next_day >= 0 {
-            String::from(format!("{}", calc_time.format("%I:%M %P =>")))
-        } else {
-            String::from(format!("{}", calc_time.format("%I:%M %P")))
+        ctx.fill();
+
+        ctx.set_source_rgba(workday_colour.0, 
+                            workday_colour.1, 
+                            workday_colour.2, 
+                            workday_colour.3);
+        // Work periods cut off by the start or end of the day are drawn past the edge so they look open
+        for (from, to) in self.model.work_hours.day_spans(&zone_day) {
+            let x_from = if from <= 0.0 { -2.0 } else { from * w };
+            let x_to = if to >= 1.0 { w + 2.0 } else { to * w };
+            ctx.rectangle(x_from, 1.0, x_to - x_from, h-2.0);
         }
-        
-    };
 
-    return ret_string;
-}
+        ctx.stroke();
 
-//Calculate the slider offset for a reference time and current component timezone
-fn get_index_from_time_string(this_tz: Option<String>, base_tz: String, for_date: NaiveDate, ref_time: &str) -> f64 {
-    let (curr_tz_start_time,_b,_c,_d) = get_current_timezone_range(base_tz, this_tz, for_date);
-    
-    let mut curr_offset: f64 = 0.0;
-    if let Some(curr_start_time_tz) = curr_tz_start_time {
-        if let Ok(time_parsed) = NaiveTime::parse_from_str(ref_time, "%H:%M") {
-            
-            let rem = time_parsed.minute() % 15;
-            let mut hour = time_parsed.hour();
-            let mut base = time_parsed.minute() / 15;
-            if (rem as f64)/15.0 > 0.5 {
-                base += 1
-            };
-            if base*15 == 60 {
-                base = 0;
-                if hour < 23 {
-                    hour = hour + 1;
-                } else {
-                    hour = 0;
-                }
-                
+        // Clock changes in this timezone, the repeated time is shaded and the size of the jump shown
+        ctx.set_font_size(10.0);
+        for change in zone_day.clock_changes.iter() {
+            let change_x = change.offset * w;
+            if change.minutes < 0 {
+                ctx.set_source_rgba(0.9, 0.1, 0.1, 0.3);
+                ctx.rectangle(change_x, 0.0, w * (-change.minutes) as f64 / zone_day.length_minutes as f64, h);
+                ctx.fill();
             }
-            curr_offset = calc_offset_for_time(curr_start_time_tz, hour, base*15 , 0) * 96.0;
-            
+            ctx.set_source_rgba(0.9, 0.1, 0.1, 0.9);
+            ctx.set_line_width(2.0);
+            ctx.move_to(change_x, 0.0);
+            ctx.line_to(change_x, h);
+            ctx.stroke();
+
+            let change_text = if change.minutes > 0 {
+                format!("{} skipped", format_minutes(change.minutes))
+            } else {
+                format!("{} repeated", format_minutes(change.minutes))
+            };
+            ctx.move_to(change_x + 3.0, h - 5.0);
+            ctx.show_text(&change_text);
         }
-    }
-    return curr_offset;
-}
 
-// Calculate slider offset for midday of current timezone
-fn calc_offset_for_midday(curr_start_time_tz: DateTime<Tz>) -> f64 {
-    return  calc_offset_for_time(curr_start_time_tz, 12, 0, 0) as f64;
-}
-
-// What proportion of the day is the start time of this timezone
-fn calc_day_percent_complete(curr_start_time_tz: DateTime<Tz>) -> f64 {
-    let full_day = NaiveTime::from_hms(23,59,59);
-    let nv_curr = NaiveTime::from_hms(curr_start_time_tz.hour(), curr_start_time_tz.minute(), curr_start_time_tz.second());
-    
-    let offset = 1.0 - (full_day - nv_curr).num_minutes() as f64 / (24.0 * 60.0);
-    
-    return offset;
-}
-
-//Calculate slider offset for current timezone and a reference time
-fn calc_offset_for_time(curr_start_time_tz: DateTime<Tz>, hour:u32, minute:u32, sec:u32) -> f64 {
-    let ref_time = NaiveTime::from_hms(hour, minute, sec);
-    let nv_curr = NaiveTime::from_hms(curr_start_time_tz.hour(), curr_start_time_tz.minute(), curr_start_time_tz.second());
-    
-    let mut offset = (ref_time - nv_curr).num_minutes();
-    if offset < 0 {
-        offset = offset + (24 * 60);
+        // Where now falls, wherever the slider has been moved to. A thin line with a notch at the
+        // top so it is not mistaken for a clock change
+        if let Some(now_offset) = self.now_offset(&zone_day) {
+            let now_x = now_offset * w;
+            ctx.set_source_rgba(0.1, 0.3, 0.9, 0.9);
+            ctx.set_line_width(1.0);
+            ctx.move_to(now_x, 0.0);
+            ctx.line_to(now_x, h);
+            ctx.stroke();
+            ctx.move_to(now_x - 4.0, 0.0);
+            ctx.line_to(now_x + 4.0, 0.0);
+            ctx.line_to(now_x, 5.0);
+            ctx.close_path();
+            ctx.fill();
+        }
+        
     }
-    
-    let index = ((offset as f64) / 15.0) / 96.0;
-    
-    return index as f64;
 }
 
 impl Update for TzSelector {
     
     type Model = TzSelectorModel;
-    type ModelParam = (i32, Option<String>, Option<String>, NaiveDate, (f64, f64, f64, f64), (f64, f64, f64, f64));
+    type ModelParam = (RowId, bool, Option<Tz>, Option<ZoneEntry>, NaiveDate, Colour, Colour, u32, u32);
     type Msg = Msg;
     
     fn update(&mut self, event: Msg) {
@@ -405,27 +519,31 @@ impl Update for TzSelector {
                 self.draw_daytime_background();
             },
             RemoveTz => {
-                self.model.local_relm.stream().emit(Msg::NotifyParentTzSelectorRemoveClicked(self.model.index));
+                self.model.local_relm.stream().emit(Msg::NotifyParentTzSelectorRemoveClicked(self.model.row_id));
             },
-            NotifyParentTzSelectorRemoveClicked(_) => {
+            NotifyParentTzSelectorRemoveClicked(_row_id) => {
                 // Dummy, message is intercepted at win but have to complete match arms here
             },
             LocalTimezoneSelect => {
-                let tz_string: String;
-                if let Some(sel_str) = self.widgets.cmb_tz_name.get_active_id() {
-                    tz_string = String::from(sel_str.as_str());
-                } else {
-                    return;
-                }
-
-                self.model.this_timezone = Some(tz_string.clone());
-                self.widgets.cmb_tz_name_entry.set_text(&tz_string);
+                let this_tz: Tz = match self.widgets.cmb_tz_name.get_active_id().and_then(|sel_str| sel_str.parse().ok()) {
+                    Some(this_tz) => this_tz,
+                    None => return,
+                };
+
+                self.model.this_timezone = Some(this_tz);
+                self.widgets.cmb_tz_name_entry.set_text(this_tz.name());
+                self.load_calendar();
+                self.update_slider_range();
                 self.update_time_labels();
                 self.update_time_display();
+                self.update_dst_warning();
+                self.widgets.draw_illum.queue_draw();
                 //Caught by parent win update loop
-                self.model.local_relm.stream().emit(Msg::NotifyParentTimezoneSelectChanged(self.model.index, tz_string.clone()));
-                if self.model.index == 0 {
-                    self.model.local_relm.stream().emit(Msg::NotifyParentBaseTzChanged(tz_string.clone()));
+                if let Some(entry) = self.get_entry() {
+                    self.model.local_relm.stream().emit(Msg::NotifyParentTimezoneSelectChanged(self.model.row_id, entry));
+                }
+                if self.model.is_base {
+                    self.model.local_relm.stream().emit(Msg::NotifyParentBaseTzChanged(this_tz));
                 }
             },
             LocalTimeSelect(value) => {
@@ -433,20 +551,37 @@ impl Update for TzSelector {
                 self.update_time_display();
             },
             TimeEntryChanged => {
-                
-                let time_val = self.widgets.txt_time_entry.get_text().to_string();
-                if time_val.len() != 5 {
+                let text = self.widgets.txt_time_entry.get_text().to_string();
+                let zone_day = self.get_display_day();
+                let selected = zone_day.time_at_index(self.widgets.slider.get_value().round(), self.model.slot_minutes);
+
+                let instant = match parse_time_entry(&text).and_then(|entry| entry.instant(selected)) {
+                    Ok(instant) => instant,
+                    // Left in the entry to be corrected
+                    Err(err) => {
+                        self.show_time_entry_error(Some(&err));
+                        return;
+                    },
+                };
+                self.widgets.txt_time_entry.set_text("");
+
+                // A time on another day takes the date with it
+                if !(0.0..1.0).contains(&zone_day.offset_for_instant(instant)) {
+                    self.model.local_relm.stream().emit(Msg::NotifyParentDateTimeSelected(instant.with_timezone(&Utc)));
                     return;
                 }
-                
-                let curr_offset = get_index_from_time_string(self.model.this_timezone.clone(), self.model.base_timezone.clone().unwrap(), self.model.for_date,  &time_val);
+                let minutes = (instant - zone_day.start).num_seconds() as f64 / 60.0;
+                let curr_offset = (minutes / self.model.slot_minutes as f64).round();
                 self.widgets.slider.set_value(curr_offset);
                 self.model.local_relm.stream().emit(Msg::NotifyParentTimeSelectChanged(curr_offset));
-                
-                self.widgets.txt_time_entry.set_text("");
-
             },
-            NotifyParentTimezoneSelectChanged(_index, _new_zone) => {
+            TimeEntryEdited => {
+                self.show_time_entry_error(None);
+            },
+            NotifyParentDateTimeSelected(_instant) => {
+                // Dummy, message is intercepted at win but have to complete match arms here
+            },
+            NotifyParentTimezoneSelectChanged(_row_id, _new_zone) => {
                 // Dummy, message is intercepted at win but have to complete match arms here
             },
             NotifyParentTimeSelectChanged(_new_value) => {
@@ -457,8 +592,12 @@ impl Update for TzSelector {
             },
             FromParentBaseTimezoneChanged(new_zone) => {
                 self.model.base_timezone = new_zone;
+                self.update_busy();
+                self.update_slider_range();
                 self.update_time_labels();
                 self.update_time_display();
+                self.update_dst_warning();
+                self.widgets.draw_illum.queue_draw();
             },
             // Should only be received by non base timezone Tz Controls
             FromParentBaseTimeSelectChanged(new_time) => {
@@ -467,8 +606,11 @@ impl Update for TzSelector {
             },
             FromParentDateChanged(new_date) => {
                 self.model.for_date = new_date;
+                self.update_busy();
+                self.update_slider_range();
                 self.update_time_labels();
                 self.update_time_display();
+                self.widgets.draw_illum.queue_draw();
             },
             FromParentColourChanged(midday, workday) => {
                 self.model.midday_colour = midday;
@@ -477,19 +619,131 @@ impl Update for TzSelector {
             },
             FromParentSetToNow => {
                 self.set_time_to_now();
-            }
+            },
+            FromParentBlockingChanged(blocking) => {
+                self.model.blocking = blocking;
+                self.widgets.draw_illum.queue_draw();
+            },
+            EditWorkHours => {
+                self.widgets.txt_work_start.set_text(&self.model.work_hours.start.format("%H:%M").to_string());
+                self.widgets.txt_work_end.set_text(&self.model.work_hours.end.format("%H:%M").to_string());
+                for (chk_day, weekday) in self.widgets.chk_work_days.iter().zip(WEEKDAYS.iter()) {
+                    chk_day.set_active(self.model.work_hours.weekdays.contains(weekday));
+                }
+                self.widgets.dlg_work_hours.show_all();
+            },
+            WorkHoursOkay => {
+                let start = NaiveTime::parse_from_str(self.widgets.txt_work_start.get_text().as_str(), "%H:%M");
+                let end = NaiveTime::parse_from_str(self.widgets.txt_work_end.get_text().as_str(), "%H:%M");
+                match (start, end) {
+                    (Ok(start), Ok(end)) => {
+                        let weekdays = WEEKDAYS.iter().zip(self.widgets.chk_work_days.iter())
+                            .filter(|(_, chk_day)| chk_day.get_active())
+                            .map(|(weekday, _)| *weekday)
+                            .collect();
+                        self.model.work_hours = WorkHours { start, end, weekdays };
+                        self.widgets.dlg_work_hours.hide();
+                        self.widgets.draw_illum.queue_draw();
+                        self.model.local_relm.stream().emit(Msg::NotifyParentWorkHoursChanged(self.model.row_id, self.model.work_hours.clone()));
+                    },
+                    //Leave the dialog open on the value that could not be read
+                    (Err(_), _) => self.widgets.txt_work_start.grab_focus(),
+                    (_, Err(_)) => self.widgets.txt_work_end.grab_focus(),
+                }
+            },
+            WorkHoursCancel => {
+                self.widgets.dlg_work_hours.hide();
+            },
+            NotifyParentWorkHoursChanged(_row_id, _work_hours) => {
+                // Dummy, message is intercepted at win but have to complete match arms here
+            },
+            EditLabel => {
+                self.widgets.txt_row_label.set_text(self.model.label.as_deref().unwrap_or(""));
+                self.widgets.chk_row_badge.set_active(self.model.show_badge);
+                self.widgets.dlg_row_label.show_all();
+            },
+            LabelOkay => {
+                let label = self.widgets.txt_row_label.get_text().trim().to_string();
+                self.model.label = if label.is_empty() { None } else { Some(label) };
+                self.model.show_badge = self.widgets.chk_row_badge.get_active();
+                self.widgets.dlg_row_label.hide();
+                self.update_row_label();
+                self.model.local_relm.stream().emit(Msg::NotifyParentLabelChanged(self.model.row_id, self.model.label.clone(), self.model.show_badge));
+            },
+            LabelCancel => {
+                self.widgets.dlg_row_label.hide();
+            },
+            NotifyParentLabelChanged(_row_id, _label, _show_badge) => {
+                // Dummy, message is intercepted at win but have to complete match arms here
+            },
+            FromParentLabelsChanged(labels) => {
+                self.update_label_column(&labels);
+            },
+            RowDropped(from_row_id) => {
+                if let Some(from_row_id) = from_row_id {
+                    if from_row_id != self.model.row_id {
+                        self.model.local_relm.stream().emit(Msg::NotifyParentRowMoved(from_row_id, self.model.row_id));
+                    }
+                }
+            },
+            NotifyParentRowMoved(_from_row_id, _to_row_id) => {
+                // Dummy, message is intercepted at win but have to complete match arms here
+            },
+            FromParentBaseChanged(is_base) => {
+                self.set_base(is_base);
+                self.update_time_display();
+                self.update_dst_warning();
+            },
+            FromParentClockTick => {
+                self.widgets.draw_illum.queue_draw();
+                if self.model.dst_warning_date != Some(self.today()) {
+                    self.update_dst_warning();
+                }
+            },
+            EditCalendar => {
+                if let Some(path) = self.model.calendar.as_ref() {
+                    self.widgets.dlg_calendar.set_filename(path);
+                }
+                self.widgets.dlg_calendar.show();
+            },
+            CalendarResponse(response) => {
+                self.widgets.dlg_calendar.hide();
+                match response {
+                    ResponseType::Accept => self.model.calendar = self.widgets.dlg_calendar.get_filename(),
+                    // Clear
+                    ResponseType::None => self.model.calendar = None,
+                    _ => return,
+                }
+                self.load_calendar();
+                self.widgets.draw_illum.queue_draw();
+                self.model.local_relm.stream().emit(Msg::NotifyParentCalendarChanged(self.model.row_id, self.model.calendar.clone()));
+            },
+            NotifyParentCalendarChanged(_row_id, _calendar) => {
+                // Dummy, message is intercepted at win but have to complete match arms here
+            },
         }
     }
 
     fn model(relm: &relm::Relm<Self>, param: Self::ModelParam) -> Self::Model {
         let local_relm = relm.clone();
-        let index = param.0;
-        let base_timezone = param.1;
-        let this_timezone = param.2;
-        let for_date = param.3;
-        let midday_colour = param.4;
-        let workday_colour = param.5;
+        let row_id = param.0;
+        let is_base = param.1;
+        let base_timezone = param.2;
+        let entry = param.3;
+        let for_date = param.4;
+        let midday_colour = param.5;
+        let workday_colour = param.6;
+        let slot_minutes = param.7;
+        let dst_warning_weeks = param.8;
+        let this_timezone = entry.as_ref().map(|entry| entry.tz);
+        let label = entry.as_ref().and_then(|entry| entry.label.clone());
+        let show_badge = entry.as_ref().map(|entry| entry.show_badge).unwrap_or(false);
+        let own_midday_colour = entry.as_ref().and_then(|entry| entry.midday_colour);
+        let own_workday_colour = entry.as_ref().and_then(|entry| entry.workday_colour);
+        let calendar = entry.as_ref().and_then(|entry| entry.calendar.clone());
+        let work_hours = entry.map(|entry| entry.work_hours).unwrap_or_default();
         let liststore = ListStore::new(&[
+            Type::String,
             Type::String
         ]);
         
@@ -497,16 +751,29 @@ impl Update for TzSelector {
         let draw_handler = DrawHandler::new().expect("draw handler");
 
         TzSelectorModel {
-            index,
+            row_id,
+            is_base,
             for_date,
             base_timezone,
             this_timezone,
+            label,
+            show_badge,
             local_relm,
             liststore,
             liststorefilter,
             draw_handler,
             midday_colour,
             workday_colour,
+            own_midday_colour,
+            own_workday_colour,
+            slot_minutes,
+            dst_warning_weeks,
+            dst_warning_date: None,
+            work_hours,
+            blocking: vec![],
+            calendar,
+            calendar_events: vec![],
+            busy: vec![],
         }
     }
 }
@@ -531,10 +798,32 @@ impl Widget for TzSelector {
         let cmb_tz_name: ComboBox = builder_widget.get_object("cmb_tz_name").expect("Could not get cmb_tz_name");
         let tz_scale_adj: Adjustment = builder_widget.get_object("tz_scale_adj").expect("Could not get tz_scale_adj");
         let lbl_current_select_time: Label = builder_widget.get_object("lbl_current_select_time").expect("Could not get lbl_current_select_time");
+        let lbl_zone_info: Label = builder_widget.get_object("lbl_zone_info").expect("Could not get lbl_zone_info");
+        let lbl_dst_warning: Label = builder_widget.get_object("lbl_dst_warning").expect("Could not get lbl_dst_warning");
         let pb_remove_tz: Button = builder_widget.get_object("pb_remove_tz").expect("Could not get pb_remove_tz");
         let draw_illum: DrawingArea = builder_widget.get_object("draw_illum").expect("Could not get draw_illum");
         let cmb_tz_name_entry: Entry = builder_widget.get_object("cmb_tz_name_entry").expect("Could not get combo entry cmb_tz_name_entry");
         let txt_time_entry: Entry = builder_widget.get_object("txt_entertime").expect("Could not get entry txt_entertime");
+        let pb_work_hours: Button = builder_widget.get_object("pb_work_hours").expect("Could not get pb_work_hours");
+        let dlg_work_hours: Dialog = builder_widget.get_object("dlg_work_hours").expect("Could not get dialog dlg_work_hours");
+        let txt_work_start: Entry = builder_widget.get_object("txt_work_start").expect("Could not get entry txt_work_start");
+        let txt_work_end: Entry = builder_widget.get_object("txt_work_end").expect("Could not get entry txt_work_end");
+        let pb_dlg_work_ok: Button = builder_widget.get_object("pb_dlg_work_ok").expect("Could not get button pb_dlg_work_ok");
+        let pb_dlg_work_cancel: Button = builder_widget.get_object("pb_dlg_work_cancel").expect("Could not get button pb_dlg_work_cancel");
+        let chk_work_days: Vec<CheckButton> = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"].iter()
+            .map(|day| builder_widget.get_object(&format!("chk_work_{}", day)).expect("Could not get working day check button"))
+            .collect();
+        let pb_row_label: Button = builder_widget.get_object("pb_row_label").expect("Could not get pb_row_label");
+        let lbl_badge: Label = builder_widget.get_object("lbl_badge").expect("Could not get lbl_badge");
+        let lbl_row_label: Label = builder_widget.get_object("lbl_row_label").expect("Could not get lbl_row_label");
+        let dlg_row_label: Dialog = builder_widget.get_object("dlg_row_label").expect("Could not get dialog dlg_row_label");
+        let txt_row_label: Entry = builder_widget.get_object("txt_row_label").expect("Could not get entry txt_row_label");
+        let chk_row_badge: CheckButton = builder_widget.get_object("chk_row_badge").expect("Could not get check button chk_row_badge");
+        let pb_dlg_label_ok: Button = builder_widget.get_object("pb_dlg_label_ok").expect("Could not get button pb_dlg_label_ok");
+        let pb_dlg_label_cancel: Button = builder_widget.get_object("pb_dlg_label_cancel").expect("Could not get button pb_dlg_label_cancel");
+        let evb_drag_handle: EventBox = builder_widget.get_object("evb_drag_handle").expect("Could not get evb_drag_handle");
+        let pb_calendar: Button = builder_widget.get_object("pb_calendar").expect("Could not get pb_calendar");
+        let dlg_calendar: FileChooserDialog = builder_widget.get_object("dlg_calendar").expect("Could not get dialog dlg_calendar");
         // let cmb_tz_name_entrycompletion: EntryCompletion = builder_widget.get_object("cmb_tz_name_entrycompletion").expect("Could not get entry completion cmb_tz_name_entrycompletion");
         let cmb_tz_name_entrycompletion: EntryCompletion = EntryCompletion::new();
         cmb_tz_name_entrycompletion.set_text_column(0);
@@ -546,7 +835,29 @@ impl Widget for TzSelector {
         connect!(relm, pb_remove_tz, connect_clicked(_), Msg::RemoveTz);
         connect!(relm, draw_illum, connect_draw(_, _), return(Msg::DrawIllumination, Inhibit(false)));
         connect!(relm, cmb_tz_name_entrycompletion, connect_match_selected(_, tm, ti), return(Msg::SearchMatchSelected(tm.clone(), ti.clone()), Inhibit(true)));
-        connect!(relm, txt_time_entry, connect_key_release_event(_, _), return(Msg::TimeEntryChanged, Inhibit(false)));
+        connect!(relm, txt_time_entry, connect_activate(_), Msg::TimeEntryChanged);
+        connect!(relm, txt_time_entry, connect_changed(_), Msg::TimeEntryEdited);
+        connect!(relm, pb_work_hours, connect_clicked(_), Msg::EditWorkHours);
+        connect!(relm, pb_dlg_work_ok, connect_clicked(_), Msg::WorkHoursOkay);
+        connect!(relm, pb_dlg_work_cancel, connect_clicked(_), Msg::WorkHoursCancel);
+        connect!(relm, pb_row_label, connect_clicked(_), Msg::EditLabel);
+        connect!(relm, pb_dlg_label_ok, connect_clicked(_), Msg::LabelOkay);
+        connect!(relm, txt_row_label, connect_activate(_), Msg::LabelOkay);
+        connect!(relm, pb_dlg_label_cancel, connect_clicked(_), Msg::LabelCancel);
+        connect!(relm, pb_calendar, connect_clicked(_), Msg::EditCalendar);
+        connect!(relm, dlg_calendar, connect_response(_, response), Msg::CalendarResponse(response));
+        // Escape would otherwise destroy the dialog, leaving nothing to show next time
+        dlg_calendar.connect_delete_event(|dialog, _| dialog.hide_on_delete());
+
+        //Rows are dragged by their handle and dropped anywhere on another row
+        let row_targets = [TargetEntry::new(ROW_DRAG_TARGET, TargetFlags::SAME_APP, 0)];
+        evb_drag_handle.drag_source_set(ModifierType::BUTTON1_MASK, &row_targets, DragAction::MOVE);
+        box_root.drag_dest_set(DestDefaults::ALL, &row_targets, DragAction::MOVE);
+        let row_id = model.row_id;
+        evb_drag_handle.connect_drag_data_get(move |_, _, data, _, _| {
+            data.set_text(&row_id.to_string());
+        });
+        connect!(relm, box_root, connect_drag_data_received(_, _, _, _, data, _, _), Msg::RowDropped(data.get_text().and_then(|text| text.parse().ok())));
 
         cmb_tz_name_entry.set_completion(Some(&cmb_tz_name_entrycompletion));
         cmb_tz_name_entrycompletion.set_model(Some(&model.liststorefilter.clone()));
@@ -557,7 +868,8 @@ impl Widget for TzSelector {
             let tm = ec.get_model().unwrap();
             match tm.get_value(ti, 0).get::<String>().unwrap() {
                 Some(str_col_value) => {
-                    if str_col_value.to_lowercase().contains(the_str) {
+                    let labels_value = tm.get_value(ti, 1).get::<String>().unwrap().unwrap_or_default();
+                    if str_col_value.to_lowercase().contains(the_str) || labels_value.to_lowercase().contains(the_str) {
                             return true;
                         } else {
                             return false;
@@ -579,11 +891,27 @@ impl Widget for TzSelector {
             cmb_tz_name,
             tz_scale_adj,
             lbl_current_select_time,
+            lbl_zone_info,
+            lbl_dst_warning,
             pb_remove_tz,
             draw_illum,
             cmb_tz_name_entrycompletion,
             cmb_tz_name_entry,
             txt_time_entry,
+            pb_work_hours,
+            dlg_work_hours,
+            txt_work_start,
+            txt_work_end,
+            chk_work_days,
+            pb_row_label,
+            lbl_badge,
+            lbl_row_label,
+            dlg_row_label,
+            txt_row_label,
+            chk_row_badge,
+            evb_drag_handle,
+            pb_calendar,
+            dlg_calendar,
         };
 
         TzSelector {
@@ -593,21 +921,18 @@ impl Widget for TzSelector {
     }
 
     fn init_view(&mut self) {
-        //First timezone widget cannto be removed
-        if self.model.index == 0 {
-            self.widgets.pb_remove_tz.set_sensitive(false);
-            self.widgets.pb_remove_tz.set_visible(false);
-        }
+        self.set_base(self.model.is_base);
+        self.update_slider_range();
         self.setup_cmb_liststore();
         self.add_timezone_strings();
         self.model.draw_handler.init(&self.widgets.draw_illum);
         self.widgets.cmb_tz_name.set_entry_text_column(0);
+        self.update_row_label();
+        self.update_dst_warning();
+        self.load_calendar();
 
-        match self.model.this_timezone.clone() {
-            Some(tz_string) => {
-                self.widgets.cmb_tz_name.set_active_id(Some(tz_string.clone().as_ref()));
-            },
-            None => {},
+        if let Some(this_tz) = self.model.this_timezone {
+            self.widgets.cmb_tz_name.set_active_id(Some(this_tz.name()));
         }
 
         let style = include_bytes!("styling.css");
@@ -627,5 +952,12 @@ impl Widget for TzSelector {
         provider.load_from_data(style).unwrap();
         style_context.add_provider(&provider, STYLE_PROVIDER_PRIORITY_APPLICATION);
 
+        for label in [&self.widgets.lbl_badge, &self.widgets.lbl_row_label, &self.widgets.lbl_zone_info, &self.widgets.lbl_dst_warning].iter() {
+            let style_context = label.get_style_context();
+            let provider = CssProvider::new();
+            provider.load_from_data(style).unwrap();
+            style_context.add_provider(&provider, STYLE_PROVIDER_PRIORITY_APPLICATION);
+        }
+
     }
 }
@@ -1,14 +1,55 @@
-use gtk::{Box, Button, ToolButton, Window, ColorChooser,};
-use gtk::{Dialog, Calendar};
+use gtk::{Box, Button, ToolButton, ToggleToolButton, Window, ColorChooser,};
+use gtk::{Dialog, Calendar, SpinButton, ListBox, Statusbar, MenuItem, MenuToolButton};
+use gtk::{ComboBoxText, MenuButton, Entry, MessageDialog, FileChooserDialog, InfoBar, Label, DrawingArea};
 
 #[derive(Clone)]
 pub struct MainWidgets {
     pub tz_box: Box,
+    pub draw_shared: DrawingArea,
     pub window: Window,
+    pub sb_status: Statusbar,
+    pub ib_save_error: InfoBar,
+    pub lbl_save_error: Label,
+    pub cmb_zone_set: ComboBoxText,
+    pub mb_zone_set: MenuButton,
+    pub mi_set_new: MenuItem,
+    pub mi_set_rename: MenuItem,
+    pub mi_set_duplicate: MenuItem,
+    pub mi_set_delete: MenuItem,
+    pub mi_set_import: MenuItem,
+    pub mi_set_export: MenuItem,
+    pub dlg_set_import: FileChooserDialog,
+    pub dlg_set_export: FileChooserDialog,
+    pub dlg_set_name: Dialog,
+    pub txt_set_name: Entry,
+    pub pb_dlg_set_ok: Button,
+    pub pb_dlg_set_cancel: Button,
+    pub dlg_set_delete: MessageDialog,
+    pub mi_sort_offset: MenuItem,
+    pub mi_sort_name: MenuItem,
+    pub mi_sort_label: MenuItem,
     pub tb_btn_add_tz: ToolButton,
+    pub tb_btn_undo_remove: ToolButton,
     pub tb_btn_sel_cal: ToolButton,
     pub tb_btn_sel_col: ToolButton,
     pub tb_btn_sel_now: ToolButton,
+    pub tb_btn_live: ToggleToolButton,
+    pub tb_btn_paste: ToolButton,
+    pub tb_btn_copy: MenuToolButton,
+    pub tb_btn_invite: ToolButton,
+    pub dlg_invite: FileChooserDialog,
+    pub txt_invite_summary: Entry,
+    pub spn_invite_duration: SpinButton,
+    pub mi_copy_plain: MenuItem,
+    pub mi_copy_markdown: MenuItem,
+    pub mi_copy_slack: MenuItem,
+    pub mi_copy_iso: MenuItem,
+    pub dlg_zone_choice: Dialog,
+    pub lbl_zone_choice: Label,
+    pub cmb_zone_choice: ComboBoxText,
+    pub pb_zone_choice_ok: Button,
+    pub pb_zone_choice_cancel: Button,
+    pub tb_btn_sort: MenuToolButton,
     pub dlg_calendar: Dialog,
     pub cal_date: Calendar,
     pub pb_dlg_cal_ok: Button,
@@ -18,5 +59,12 @@ pub struct MainWidgets {
     pub dlg_col_col_workday: ColorChooser,
     pub pb_dlg_col_ok: Button, 
     pub pb_dlg_col_cancel: Button,
+    pub tb_btn_find_meeting: ToolButton,
+    pub dlg_meeting: Dialog,
+    pub spn_meet_duration: SpinButton,
+    pub spn_meet_days: SpinButton,
+    pub lst_meeting_results: ListBox,
+    pub pb_dlg_meet_find: Button,
+    pub pb_dlg_meet_close: Button,
 }
 
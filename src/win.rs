@@ -1,29 +1,49 @@
-use relm::{Relm, Update, Widget, Channel, WidgetTest};
+use relm::{Component, Relm, Update, Widget, Channel, WidgetTest, DrawHandler};
 use gtk::prelude::*;
 use gtk::{Window, Builder, Box, 
     ToolButton, Dialog, Button, Calendar, STYLE_PROVIDER_PRIORITY_APPLICATION, CssProvider,
-    ColorChooser,
+    ColorChooser, SpinButton, ListBox, Label, Statusbar, MenuItem, MenuToolButton,
+    ComboBoxText, MenuButton, Entry, MessageDialog, ResponseType, FileChooserDialog, InfoBar, HeaderBar,
+    ToggleToolButton, Clipboard, DrawingArea, Container,
 };
-use gdk::{RGBA};
-use chrono::{NaiveDate, Local, Datelike};
+use std::path::PathBuf;
+use gdk::{RGBA, SELECTION_CLIPBOARD};
+use std::cmp::Ordering;
+use chrono::{DateTime, NaiveDate, Local, Datelike, Duration, Utc};
+use chrono_tz::Tz;
 use crate::relm::ContainerWidget;
 use crate::model::*;
 use crate::widgets::MainWidgets;
 use crate::tzselector::*;
 use crate::app::{Msg, MsgUpdateType};
+use crate::config::{ConfigOptions, load_config, save_config};
+use crate::zone_file::{ZoneFileFormat, export_zone_set, import_zone_sets};
+use timezoners_core::{SLOT_SIZES, DEFAULT_SLOT_MINUTES, SavedZone, WorkHours, ZoneEntry, ZoneSet, ZoneSort, compare_entries, day_overlap, find_meeting_windows, index_from_time, start_of_day, format_minutes, unique_set_name,
+    CopyFormat, EntryZone, Invite, invite_to_ics, TimeEntry, abbreviation_meanings, format_times, find_time_entry, format_utc_offset};
+
+// How long to wait after a change before saving, so a burst of changes is saved once
+const SAVE_DELAY_MS: u32 = 1000;
+
+// How often the now markers are moved along and live mode checks the clock, the markers move a
+// pixel or so each time. Ticks also come just after each minute starts, see schedule_clock_tick
+const CLOCK_TICK_MS: u32 = 15_000;
 
 pub struct Win {
     pub model: Model,
     widgets: MainWidgets,
     config: Config,
+    // None when there is nowhere to keep settings
+    config_path: Option<PathBuf>,
+    // Nothing is saved to config_path
+    read_only: bool,
 }
 
 impl Update for Win {
     type Model = Model;
-    type ModelParam = ();
+    type ModelParam = ConfigOptions;
     type Msg = Msg;
 
-    fn model(relm: &Relm<Self>, _: ()) -> Model {
+    fn model(relm: &Relm<Self>, config_options: ConfigOptions) -> Model {
         
         let stream = relm.stream().clone();
         let (_channel, sender) = Channel::new(move |upd_tuple| {
@@ -31,16 +51,28 @@ impl Update for Win {
             // We send a message to the current widget.
             stream.emit(Msg::ProcessUpdateMsg(upd_tuple));
         });
-        let local_now = Local::now();
-        let for_date = NaiveDate::from_ymd(local_now.year(), local_now.month(), local_now.day());
+        let for_date = Local::now().date_naive();
 
         Model {
             tz_ctrls: vec![],
             tz_zones: vec![],
+            tz_row_ids: vec![],
+            next_row_id: 0,
+            removed_row: None,
             sender,
             local_relm: relm.clone(),
             base_tz: None,
             for_date,
+            shared: vec![],
+            shared_draw_handler: DrawHandler::new().expect("draw handler"),
+            selected_slot: 0.0,
+            meeting_windows: vec![],
+            set_name_edit: ZoneSetEdit::Create,
+            save_generation: 0,
+            live: false,
+            live_slot: None,
+            pending_paste: None,
+            config_options,
         }
     }
 
@@ -56,18 +88,20 @@ impl Update for Win {
                 }
             },
             Quit => {
-                self.config.zones.clear();
-                for i in 0..self.model.tz_zones.len() {
-                    if let Some(tz_zone) = self.model.tz_zones[i].clone() {
-                        self.config.zones.push(Some(tz_zone));
-                    }
-                    
-                }
                 self.save_config();
                 gtk::main_quit();
             },
-            AddTzSelector(tz_location) => {
-                self.add_tz_selector(tz_location);
+            AddTzSelector(entry) => {
+                self.add_tz_selector(entry);
+            },
+            UndoRemove => {
+                if let Some((position, entry)) = self.model.removed_row.take() {
+                    self.add_tz_selector(entry);
+                    let last = self.model.tz_ctrls.len() - 1;
+                    self.move_row(last, position.min(last));
+                    self.queue_save();
+                }
+                self.widgets.tb_btn_undo_remove.set_sensitive(false);
             },
             SelectDate => {
                 self.widgets.cal_date.select_month(self.model.for_date.month()-1, self.model.for_date.year() as u32);
@@ -76,12 +110,9 @@ impl Update for Win {
             },
             DateOkay => {
                 let (y,m,d) = self.widgets.cal_date.get_date();
-                self.model.for_date = NaiveDate::from_ymd(y as i32, m + 1, d);
                 self.widgets.dlg_calendar.hide();
-                self.widgets.tb_btn_sel_cal.set_label(Some(format!("{}", self.model.for_date.format("On %Y/%m/%d")).as_ref()));
-                for i in 1..self.model.tz_ctrls.len() {
-                    self.model.tz_ctrls[i].emit(crate::tzselector::Msg::FromParentDateChanged(self.model.for_date));
-                };
+                self.stop_live();
+                self.set_for_date(NaiveDate::from_ymd_opt(y as i32, m + 1, d).unwrap());
             },
             DateCancel => {
                 self.widgets.dlg_calendar.hide();
@@ -108,34 +139,251 @@ impl Update for Win {
                 for i in 0..self.model.tz_ctrls.len() {
                     self.model.tz_ctrls[i].emit(crate::tzselector::Msg::FromParentColourChanged(self.config.midday_colour, self.config.workday_colour));
                 }
+                self.queue_save();
             },
             ColourCancel => {
                 self.widgets.dlg_colour.hide();
             },
+            FindMeeting => {
+                self.widgets.dlg_meeting.show_all();
+            },
+            MeetingSearch => {
+                self.search_meetings();
+            },
+            MeetingSelected(row_index) => {
+                if let Some(window) = self.model.meeting_windows.get(row_index as usize).cloned() {
+                    self.stop_live();
+                    if window.for_date != self.model.for_date {
+                        self.set_for_date(window.for_date);
+                    }
+                    // Rounded to the nearest slot as for any other time selected
+                    let base_tz = window.start.timezone();
+                    let index = index_from_time(base_tz, base_tz, window.for_date, window.start.time(), self.config.slot_minutes);
+                    self.model.local_relm.stream().emit(Msg::TimeSelectChanged(index));
+                }
+            },
+            MeetingClose => {
+                self.widgets.dlg_meeting.hide();
+            },
+            SortRows(sort) => {
+                self.sort_rows(sort);
+            },
+            ZoneSetSelected(set_name) => {
+                // Also sent while the list is being refilled, which leaves the active set alone
+                if let Some(set_name) = set_name.filter(|set_name| *set_name != self.config.active_set) {
+                    self.show_zone_set(&set_name);
+                }
+            },
+            EditZoneSet(set_name_edit) => {
+                let active_name = self.config.active_set.clone();
+                let (title, name) = match set_name_edit {
+                    ZoneSetEdit::Create => ("New zone set", String::new()),
+                    ZoneSetEdit::Rename => ("Rename zone set", active_name),
+                    ZoneSetEdit::Duplicate => ("Duplicate zone set", format!("{} copy", active_name)),
+                };
+                self.model.set_name_edit = set_name_edit;
+                self.widgets.dlg_set_name.set_title(title);
+                self.widgets.txt_set_name.set_text(&name);
+                self.widgets.dlg_set_name.show_all();
+            },
+            ZoneSetNameOkay => {
+                let name = self.widgets.txt_set_name.get_text().trim().to_string();
+                self.widgets.dlg_set_name.hide();
+                if !name.is_empty() {
+                    self.apply_zone_set_name(&name);
+                }
+            },
+            ZoneSetNameCancel => {
+                self.widgets.dlg_set_name.hide();
+            },
+            DeleteZoneSet => {
+                // There is always one set left to show
+                if self.config.zone_sets.len() > 1 {
+                    self.widgets.dlg_set_delete.set_property_text(Some(&format!("Delete the zone set \"{}\"?", self.config.active_set)));
+                    self.widgets.dlg_set_delete.show();
+                }
+            },
+            ImportZoneSets => {
+                self.widgets.dlg_set_import.show();
+            },
+            ImportZoneSetsResponse(accepted) => {
+                self.widgets.dlg_set_import.hide();
+                if let (true, Some(path)) = (accepted, self.widgets.dlg_set_import.get_filename()) {
+                    self.import_zone_sets(path);
+                }
+            },
+            ExportZoneSet => {
+                self.store_active_set();
+                self.widgets.dlg_set_export.set_current_name(&format!("{}.toml", self.config.active_set));
+                self.widgets.dlg_set_export.show();
+            },
+            ExportZoneSetResponse(accepted) => {
+                self.widgets.dlg_set_export.hide();
+                if let (true, Some(path)) = (accepted, self.widgets.dlg_set_export.get_filename()) {
+                    self.export_zone_set(path);
+                }
+            },
+            SaveConfig(save_generation) => {
+                // Another change has come in since this save was queued, its own save follows
+                if save_generation == self.model.save_generation {
+                    self.save_config();
+                }
+            },
+            SaveErrorResponse(retry) => {
+                self.widgets.ib_save_error.hide();
+                if retry {
+                    self.save_config();
+                }
+            },
+            DeleteZoneSetResponse(delete) => {
+                self.widgets.dlg_set_delete.hide();
+                if let (true, Some(index)) = (delete, self.config.active_set_index()) {
+                    self.config.zone_sets.remove(index);
+                    let next_name = self.config.zone_sets[index.min(self.config.zone_sets.len() - 1)].name.clone();
+                    self.show_zone_set(&next_name);
+                }
+            },
             //Messages from child components
-            TimezoneSelectChanged(index, new_zone) => {
-                self.model.tz_zones[index as usize] = Some(new_zone);
+            TimezoneSelectChanged(row_id, new_zone) => {
+                if let Some(position) = self.row_position(row_id) {
+                    self.model.tz_zones[position] = Some(new_zone);
+                    self.update_overlap();
+                    self.update_labels();
+                    self.queue_save();
+                }
             },
             TimeSelectChanged(new_time) => {
+                self.model.selected_slot = new_time;
                 for i in 0..self.model.tz_ctrls.len() {
                     self.model.tz_ctrls[i].emit(crate::tzselector::Msg::FromParentBaseTimeSelectChanged(new_time));
                 }
             },
-            TimezoneRemove(remove_index) => {
-                let rem_widget = self.model.tz_ctrls.get(remove_index as usize).unwrap();
-                self.widgets.tz_box.remove::<Box>(rem_widget.widget());
-                self.model.tz_zones[remove_index as usize] = None;
+            // A time typed into a row that falls on another base date
+            DateTimeSelected(instant) => {
+                if let Some(base_tz) = self.model.base_tz {
+                    let base_instant = instant.with_timezone(&base_tz);
+                    self.stop_live();
+                    self.set_for_date(base_instant.naive_local().date());
+                    let day_start = start_of_day(base_tz, self.model.for_date);
+                    let minutes = (base_instant - day_start).num_seconds() as f64 / 60.0;
+                    let index = (minutes / self.config.slot_minutes as f64).round();
+                    self.model.local_relm.stream().emit(Msg::TimeSelectChanged(index));
+                }
+            },
+            CalendarChanged(row_id, calendar) => {
+                if let Some(entry) = self.row_position(row_id).and_then(|position| self.model.tz_zones[position].as_mut()) {
+                    entry.calendar = calendar;
+                }
+                self.queue_save();
+            },
+            WorkHoursChanged(row_id, work_hours) => {
+                // Rows without a timezone yet keep their working hours until one is selected
+                if let Some(entry) = self.row_position(row_id).and_then(|position| self.model.tz_zones[position].as_mut()) {
+                    entry.work_hours = work_hours;
+                }
+                self.update_overlap();
+                self.queue_save();
+            },
+            LabelChanged(row_id, label, show_badge) => {
+                if let Some(entry) = self.row_position(row_id).and_then(|position| self.model.tz_zones[position].as_mut()) {
+                    entry.label = label;
+                    entry.show_badge = show_badge;
+                }
+                self.update_labels();
+                self.queue_save();
+            },
+            RowMoved(from_row_id, to_row_id) => {
+                if let (Some(from_position), Some(to_position)) = (self.row_position(from_row_id), self.row_position(to_row_id)) {
+                    self.move_row(from_position, to_position);
+                }
+            },
+            TimezoneRemove(row_id) => {
+                if let Some(position) = self.row_position(row_id) {
+                    let rem_ctrl = self.model.tz_ctrls.remove(position);
+                    self.widgets.tz_box.remove::<Box>(rem_ctrl.widget());
+                    self.model.tz_row_ids.remove(position);
+                    let entry = self.model.tz_zones.remove(position);
+                    // Only the last row removed can be put back
+                    self.model.removed_row = Some((position, entry));
+                    self.widgets.tb_btn_undo_remove.set_sensitive(true);
+                    self.update_overlap();
+                    self.update_labels();
+                    self.queue_save();
+                }
             },
             //Messages to child componenets
             ChangeBaseTimezone(new_zone) => {
-                self.model.base_tz = new_zone.clone();
+                self.model.base_tz = new_zone;
                 for i in 0..self.model.tz_ctrls.len() {
-                    self.model.tz_ctrls[i].emit(crate::tzselector::Msg::FromParentBaseTimezoneChanged(new_zone.clone()));
+                    self.model.tz_ctrls[i].emit(crate::tzselector::Msg::FromParentBaseTimezoneChanged(new_zone));
                 }
+                self.update_overlap();
             },
             SetToNow => {
                 self.model.tz_ctrls[0].emit(crate::tzselector::Msg::FromParentSetToNow);
             },
+            LiveToggled(live) => {
+                self.model.live = live;
+                self.model.live_slot = None;
+                if live {
+                    self.follow_clock();
+                }
+            },
+            PasteTime => {
+                let clipboard = Clipboard::get(&SELECTION_CLIPBOARD);
+                match clipboard.wait_for_text().map(|text| find_time_entry(&text)) {
+                    Some(Ok(entry)) => self.paste_time_entry(entry),
+                    Some(Err(err)) => self.show_status("paste", &format!("Could not paste a time, {}", err)),
+                    None => self.show_status("paste", "There is no text to paste a time from"),
+                }
+            },
+            ZoneChoiceOkay => {
+                self.widgets.dlg_zone_choice.hide();
+                if let Some(mut entry) = self.model.pending_paste.take() {
+                    let meanings = match entry.zone.as_ref() {
+                        Some(EntryZone::Abbreviation(abbreviation)) => abbreviation_meanings(abbreviation),
+                        _ => vec![],
+                    };
+                    let choice = self.widgets.cmb_zone_choice.get_active().and_then(|index| meanings.get(index as usize));
+                    if let Some(meaning) = choice {
+                        entry.zone = Some(EntryZone::Offset(meaning.utc_offset_minutes));
+                        self.paste_time_entry(entry);
+                    }
+                }
+            },
+            CopyTime(format) => {
+                self.copy_selected_time(format);
+            },
+            ExportInvite => {
+                if let Some(start) = self.selected_instant() {
+                    self.widgets.dlg_invite.set_current_name(&format!("{}.ics", start.format("%Y-%m-%d %H%M")));
+                    self.widgets.dlg_invite.show();
+                } else {
+                    self.show_status("invite", "Choose a base timezone before exporting an invite");
+                }
+            },
+            ExportInviteResponse(accepted) => {
+                self.widgets.dlg_invite.hide();
+                if let (true, Some(path)) = (accepted, self.widgets.dlg_invite.get_filename()) {
+                    self.export_invite(path);
+                }
+            },
+            ZoneChoiceCancel => {
+                self.widgets.dlg_zone_choice.hide();
+                self.model.pending_paste = None;
+            },
+            ClockTick => {
+                if self.model.live {
+                    self.follow_clock();
+                }
+                for tz_ctrl in self.model.tz_ctrls.iter() {
+                    tz_ctrl.emit(crate::tzselector::Msg::FromParentClockTick);
+                }
+                self.schedule_clock_tick();
+            },
+            DrawShared => {
+                self.draw_shared();
+            },
         }
     }
     
@@ -151,32 +399,78 @@ impl Widget for Win {
     }
 
     fn view(relm: &Relm<Self>, mut model: Self::Model) -> Self {
-        let mut base_tz: Option<String> = None;
-
-        let config: Config = match confy::load("TimezoneRS") {
-            Ok(x) =>  x,
-            Err(_) => Config::default(),
+        let config_options = model.config_options.clone();
+        let config_path = config_options.config_path();
+        let (mut config, mut config_problem, can_save) = match config_path.as_ref() {
+            Some(path) => load_config(path),
+            None => (Config::default(), Some("Could not find a folder to keep settings in, changes will not be saved".to_string()), true),
         };
 
-        if config.zones.len() > 0 {
-            if let Some(tz_string) = config.zones[0].clone() {
-                base_tz = Some(tz_string.clone());
-            }
-            
+        if !SLOT_SIZES.contains(&config.slot_minutes) {
+            config.slot_minutes = DEFAULT_SLOT_MINUTES;
         }
-        model.base_tz = base_tz.clone();
+
+        let (zone_entries, _) = config.zone_entries();
+        let base_entry = zone_entries.into_iter().next();
+        model.base_tz = base_entry.as_ref().map(|entry| entry.tz);
 
         let glade_src_main = include_str!("timezoners_gui.glade");
         let builder_main = Builder::from_string(glade_src_main);
 
         //Main window
         let window: Window = builder_main.get_object("main_window").expect("Couldn't get Main Window");
+        let hb_main: HeaderBar = builder_main.get_object("hb_main").expect("Couldn't get header bar hb_main");
+        hb_main.set_subtitle(config_options.description().as_deref());
+        if config_options.read_only && config_problem.is_none() {
+            config_problem = Some("Settings are read only, changes will not be saved".to_string());
+        }
         let tz_box: Box = builder_main.get_object("box_widgets").expect("Could not get the widgets box");
+        let draw_shared: DrawingArea = builder_main.get_object("draw_shared").expect("Could not get draw_shared");
+        let sb_status: Statusbar = builder_main.get_object("sb_status").expect("Could not get status bar sb_status");
+        let ib_save_error: InfoBar = builder_main.get_object("ib_save_error").expect("Could not get info bar ib_save_error");
+        let lbl_save_error: Label = builder_main.get_object("lbl_save_error").expect("Could not get label lbl_save_error");
         let tb_btn_sel_exit: ToolButton = builder_main.get_object("tb_btn_sel_exit").expect("Couldn't get exit button tb_btn_sel_exit");
         let tb_btn_add_tz: ToolButton = builder_main.get_object("tb_btn_add_tz").expect("Could not get tb_btn_add_tz");
+        let tb_btn_undo_remove: ToolButton = builder_main.get_object("tb_btn_undo_remove").expect("Could not get tb_btn_undo_remove");
         let tb_btn_sel_cal: ToolButton = builder_main.get_object("tb_btn_sel_cal").expect("Could not geto tb_btn_sel_cal");
         let tb_btn_sel_col: ToolButton = builder_main.get_object("tb_btn_sel_col").expect("Could not get tool button tb_btn_sel_col");
         let tb_btn_sel_now: ToolButton = builder_main.get_object("tb_btn_sel_now").expect("Could not get tool button tb_btn_sel_now");
+        let tb_btn_live: ToggleToolButton = builder_main.get_object("tb_btn_live").expect("Could not get tool button tb_btn_live");
+        let tb_btn_paste: ToolButton = builder_main.get_object("tb_btn_paste").expect("Could not get tool button tb_btn_paste");
+        let tb_btn_copy: MenuToolButton = builder_main.get_object("tb_btn_copy").expect("Could not get tool button tb_btn_copy");
+        let tb_btn_invite: ToolButton = builder_main.get_object("tb_btn_invite").expect("Could not get tool button tb_btn_invite");
+        let dlg_invite: FileChooserDialog = builder_main.get_object("dlg_invite").expect("Could not get dialog dlg_invite");
+        let txt_invite_summary: Entry = builder_main.get_object("txt_invite_summary").expect("Could not get entry txt_invite_summary");
+        let spn_invite_duration: SpinButton = builder_main.get_object("spn_invite_duration").expect("Could not get spin button spn_invite_duration");
+        let mi_copy_plain: MenuItem = builder_main.get_object("mi_copy_plain").expect("Could not get menu item mi_copy_plain");
+        let mi_copy_markdown: MenuItem = builder_main.get_object("mi_copy_markdown").expect("Could not get menu item mi_copy_markdown");
+        let mi_copy_slack: MenuItem = builder_main.get_object("mi_copy_slack").expect("Could not get menu item mi_copy_slack");
+        let mi_copy_iso: MenuItem = builder_main.get_object("mi_copy_iso").expect("Could not get menu item mi_copy_iso");
+        let dlg_zone_choice: Dialog = builder_main.get_object("dlg_zone_choice").expect("Could not get dialog dlg_zone_choice");
+        let lbl_zone_choice: Label = builder_main.get_object("lbl_zone_choice").expect("Could not get label lbl_zone_choice");
+        let cmb_zone_choice: ComboBoxText = builder_main.get_object("cmb_zone_choice").expect("Could not get combo cmb_zone_choice");
+        let pb_zone_choice_ok: Button = builder_main.get_object("pb_zone_choice_ok").expect("Could not get button pb_zone_choice_ok");
+        let pb_zone_choice_cancel: Button = builder_main.get_object("pb_zone_choice_cancel").expect("Could not get button pb_zone_choice_cancel");
+        let tb_btn_sort: MenuToolButton = builder_main.get_object("tb_btn_sort").expect("Could not get tool button tb_btn_sort");
+        let mi_sort_offset: MenuItem = builder_main.get_object("mi_sort_offset").expect("Could not get menu item mi_sort_offset");
+        let mi_sort_name: MenuItem = builder_main.get_object("mi_sort_name").expect("Could not get menu item mi_sort_name");
+        let mi_sort_label: MenuItem = builder_main.get_object("mi_sort_label").expect("Could not get menu item mi_sort_label");
+
+        let cmb_zone_set: ComboBoxText = builder_main.get_object("cmb_zone_set").expect("Could not get combo box cmb_zone_set");
+        let mb_zone_set: MenuButton = builder_main.get_object("mb_zone_set").expect("Could not get menu button mb_zone_set");
+        let mi_set_new: MenuItem = builder_main.get_object("mi_set_new").expect("Could not get menu item mi_set_new");
+        let mi_set_rename: MenuItem = builder_main.get_object("mi_set_rename").expect("Could not get menu item mi_set_rename");
+        let mi_set_duplicate: MenuItem = builder_main.get_object("mi_set_duplicate").expect("Could not get menu item mi_set_duplicate");
+        let mi_set_delete: MenuItem = builder_main.get_object("mi_set_delete").expect("Could not get menu item mi_set_delete");
+        let dlg_set_name: Dialog = builder_main.get_object("dlg_set_name").expect("Could not get dialog dlg_set_name");
+        let txt_set_name: Entry = builder_main.get_object("txt_set_name").expect("Could not get entry txt_set_name");
+        let pb_dlg_set_ok: Button = builder_main.get_object("pb_dlg_set_ok").expect("Could not get button pb_dlg_set_ok");
+        let pb_dlg_set_cancel: Button = builder_main.get_object("pb_dlg_set_cancel").expect("Could not get button pb_dlg_set_cancel");
+        let dlg_set_delete: MessageDialog = builder_main.get_object("dlg_set_delete").expect("Could not get dialog dlg_set_delete");
+        let mi_set_import: MenuItem = builder_main.get_object("mi_set_import").expect("Could not get menu item mi_set_import");
+        let mi_set_export: MenuItem = builder_main.get_object("mi_set_export").expect("Could not get menu item mi_set_export");
+        let dlg_set_import: FileChooserDialog = builder_main.get_object("dlg_set_import").expect("Could not get dialog dlg_set_import");
+        let dlg_set_export: FileChooserDialog = builder_main.get_object("dlg_set_export").expect("Could not get dialog dlg_set_export");
         
         let dlg_calendar: Dialog = builder_main.get_object("dlg_calendar").expect("Could not get dialog dlg_calendar");
         let cal_date: Calendar = builder_main.get_object("cal_date").expect("Could not get cal_date");
@@ -189,6 +483,14 @@ impl Widget for Win {
         let pb_dlg_col_ok: Button = builder_main.get_object("pb_dlg_col_ok").expect("Could not get button pb_dlg_col_ok");
         let pb_dlg_col_cancel: Button = builder_main.get_object("pb_dlg_col_cancel").expect("Could not get button pb_dlg_col_cancel");
 
+        let tb_btn_find_meeting: ToolButton = builder_main.get_object("tb_btn_find_meeting").expect("Could not get tool button tb_btn_find_meeting");
+        let dlg_meeting: Dialog = builder_main.get_object("dlg_meeting").expect("Could not get dialog dlg_meeting");
+        let spn_meet_duration: SpinButton = builder_main.get_object("spn_meet_duration").expect("Could not get spin button spn_meet_duration");
+        let spn_meet_days: SpinButton = builder_main.get_object("spn_meet_days").expect("Could not get spin button spn_meet_days");
+        let lst_meeting_results: ListBox = builder_main.get_object("lst_meeting_results").expect("Could not get list lst_meeting_results");
+        let pb_dlg_meet_find: Button = builder_main.get_object("pb_dlg_meet_find").expect("Could not get button pb_dlg_meet_find");
+        let pb_dlg_meet_close: Button = builder_main.get_object("pb_dlg_meet_close").expect("Could not get button pb_dlg_meet_close");
+
 
         let midday_colour = (
             config.midday_colour.0, 
@@ -202,40 +504,134 @@ impl Widget for Win {
             config.workday_colour.2,
             config.workday_colour.3);
         
-        let first_selector = tz_box.add_widget::<TzSelector>((0, base_tz.clone(), base_tz.clone(), model.for_date.clone(), midday_colour, workday_colour));
+        let first_selector = tz_box.add_widget::<TzSelector>((model.next_row_id, true, model.base_tz, base_entry.clone(), model.for_date.clone(), midday_colour, workday_colour, config.slot_minutes, config.dst_warning_weeks));
         
-        connect!(first_selector@crate::tzselector::Msg::NotifyParentTimezoneSelectChanged(index, ref new_zone), relm, Msg::TimezoneSelectChanged(index, new_zone.clone()));
+        connect!(first_selector@crate::tzselector::Msg::NotifyParentTimezoneSelectChanged(row_id, ref new_zone), relm, Msg::TimezoneSelectChanged(row_id, new_zone.clone()));
         connect!(first_selector@crate::tzselector::Msg::NotifyParentTimeSelectChanged(new_time), relm, Msg::TimeSelectChanged(new_time));
-        connect!(first_selector@crate::tzselector::Msg::NotifyParentBaseTzChanged(ref new_zone), relm, Msg::ChangeBaseTimezone(Some(new_zone.clone())));
+        connect!(first_selector@crate::tzselector::Msg::NotifyParentDateTimeSelected(instant), relm, Msg::DateTimeSelected(instant));
+        connect!(first_selector@crate::tzselector::Msg::NotifyParentBaseTzChanged(new_zone), relm, Msg::ChangeBaseTimezone(Some(new_zone)));
+        connect!(first_selector@crate::tzselector::Msg::NotifyParentWorkHoursChanged(row_id, ref work_hours), relm, Msg::WorkHoursChanged(row_id, work_hours.clone()));
+        connect!(first_selector@crate::tzselector::Msg::NotifyParentCalendarChanged(row_id, ref calendar), relm, Msg::CalendarChanged(row_id, calendar.clone()));
+        connect!(first_selector@crate::tzselector::Msg::NotifyParentLabelChanged(row_id, ref label, show_badge), relm, Msg::LabelChanged(row_id, label.clone(), show_badge));
+        connect!(first_selector@crate::tzselector::Msg::NotifyParentRowMoved(from_row_id, to_row_id), relm, Msg::RowMoved(from_row_id, to_row_id));
+        connect!(first_selector@crate::tzselector::Msg::NotifyParentTzSelectorRemoveClicked(row_id), relm, Msg::TimezoneRemove(row_id));
         
         
         model.tz_ctrls.push(first_selector);
-        model.tz_zones.push(base_tz);
+        model.tz_zones.push(base_entry);
+        model.tz_row_ids.push(model.next_row_id);
+        model.next_row_id += 1;
         
         connect!(relm, window, connect_delete_event(_, _), return (Some(Msg::Quit), Inhibit(false)));
         connect!(relm, tb_btn_sel_exit, connect_clicked(_), Msg::Quit);
-        connect!(relm, tb_btn_add_tz, connect_clicked(_), Msg::AddTzSelector(String::from("")));
+        connect!(relm, tb_btn_add_tz, connect_clicked(_), Msg::AddTzSelector(None));
+        connect!(relm, tb_btn_undo_remove, connect_clicked(_), Msg::UndoRemove);
         connect!(relm, tb_btn_sel_cal, connect_clicked(_), Msg::SelectDate);
         connect!(relm, pb_dlg_cal_ok, connect_clicked(_), Msg::DateOkay);
         connect!(relm, pb_dlg_cal_cancel, connect_clicked(_), Msg::DateCancel);
 
         connect!(relm, tb_btn_sel_col, connect_clicked(_), Msg::SelectColour);
         connect!(relm, tb_btn_sel_now, connect_clicked(_), Msg::SetToNow);
+        connect!(relm, tb_btn_live, connect_toggled(btn), Msg::LiveToggled(btn.get_active()));
+        connect!(relm, tb_btn_paste, connect_clicked(_), Msg::PasteTime);
+        connect!(relm, tb_btn_copy, connect_clicked(_), Msg::CopyTime(CopyFormat::PlainText));
+        connect!(relm, tb_btn_invite, connect_clicked(_), Msg::ExportInvite);
+        connect!(relm, dlg_invite, connect_response(_, response), Msg::ExportInviteResponse(response == ResponseType::Accept));
+        dlg_invite.connect_delete_event(|dialog, _| dialog.hide_on_delete());
+        connect!(relm, mi_copy_plain, connect_activate(_), Msg::CopyTime(CopyFormat::PlainText));
+        connect!(relm, mi_copy_markdown, connect_activate(_), Msg::CopyTime(CopyFormat::Markdown));
+        connect!(relm, mi_copy_slack, connect_activate(_), Msg::CopyTime(CopyFormat::Slack));
+        connect!(relm, mi_copy_iso, connect_activate(_), Msg::CopyTime(CopyFormat::Iso8601));
+        connect!(relm, pb_zone_choice_ok, connect_clicked(_), Msg::ZoneChoiceOkay);
+        connect!(relm, pb_zone_choice_cancel, connect_clicked(_), Msg::ZoneChoiceCancel);
         connect!(relm, pb_dlg_col_ok, connect_clicked(_), Msg::ColourOkay);
         connect!(relm, pb_dlg_col_cancel, connect_clicked(_), Msg::ColourCancel);
+        connect!(relm, tb_btn_find_meeting, connect_clicked(_), Msg::FindMeeting);
+        connect!(relm, tb_btn_sort, connect_clicked(_), Msg::SortRows(ZoneSort::UtcOffset));
+        connect!(relm, mi_sort_offset, connect_activate(_), Msg::SortRows(ZoneSort::UtcOffset));
+        connect!(relm, mi_sort_name, connect_activate(_), Msg::SortRows(ZoneSort::Alphabetical));
+        connect!(relm, mi_sort_label, connect_activate(_), Msg::SortRows(ZoneSort::Label));
+        connect!(relm, pb_dlg_meet_find, connect_clicked(_), Msg::MeetingSearch);
+        connect!(relm, pb_dlg_meet_close, connect_clicked(_), Msg::MeetingClose);
+        connect!(relm, lst_meeting_results, connect_row_activated(_, row), Msg::MeetingSelected(row.get_index()));
+        connect!(relm, cmb_zone_set, connect_changed(cmb), Msg::ZoneSetSelected(cmb.get_active_text().map(|text| text.to_string())));
+        connect!(relm, mi_set_new, connect_activate(_), Msg::EditZoneSet(ZoneSetEdit::Create));
+        connect!(relm, mi_set_rename, connect_activate(_), Msg::EditZoneSet(ZoneSetEdit::Rename));
+        connect!(relm, mi_set_duplicate, connect_activate(_), Msg::EditZoneSet(ZoneSetEdit::Duplicate));
+        connect!(relm, mi_set_delete, connect_activate(_), Msg::DeleteZoneSet);
+        connect!(relm, pb_dlg_set_ok, connect_clicked(_), Msg::ZoneSetNameOkay);
+        connect!(relm, txt_set_name, connect_activate(_), Msg::ZoneSetNameOkay);
+        connect!(relm, pb_dlg_set_cancel, connect_clicked(_), Msg::ZoneSetNameCancel);
+        connect!(relm, dlg_set_delete, connect_response(_, response), Msg::DeleteZoneSetResponse(response == ResponseType::Ok));
+        // Escape would otherwise destroy the dialog, leaving nothing to show next time
+        dlg_set_delete.connect_delete_event(|dialog, _| dialog.hide_on_delete());
+        connect!(relm, ib_save_error, connect_response(_, response), Msg::SaveErrorResponse(response == ResponseType::Apply));
+        connect!(relm, mi_set_import, connect_activate(_), Msg::ImportZoneSets);
+        connect!(relm, mi_set_export, connect_activate(_), Msg::ExportZoneSet);
+        connect!(relm, dlg_set_import, connect_response(_, response), Msg::ImportZoneSetsResponse(response == ResponseType::Accept));
+        connect!(relm, dlg_set_export, connect_response(_, response), Msg::ExportZoneSetResponse(response == ResponseType::Accept));
+        dlg_set_import.connect_delete_event(|dialog, _| dialog.hide_on_delete());
+        dlg_set_export.connect_delete_event(|dialog, _| dialog.hide_on_delete());
+        connect!(relm, draw_shared, connect_draw(_, _), return(Msg::DrawShared, Inhibit(false)));
         
         window.show_all();
         window.move_(config.win_pos_x, config.win_pos_y);
         window.resize(config.win_width, config.win_height);
         window.present();
-        
+
+        // Left in the status bar so it is seen even if the window takes a while to appear
+        if let Some(config_problem) = config_problem {
+            let context_id = sb_status.get_context_id("config_load");
+            sb_status.push(context_id, &config_problem);
+        }
+
         let widgets = MainWidgets {
             tz_box,
+            draw_shared,
             window,
+            sb_status,
+            ib_save_error,
+            lbl_save_error,
+            cmb_zone_set,
+            mb_zone_set,
+            mi_set_new,
+            mi_set_rename,
+            mi_set_duplicate,
+            mi_set_delete,
+            dlg_set_name,
+            txt_set_name,
+            pb_dlg_set_ok,
+            pb_dlg_set_cancel,
+            dlg_set_delete,
+            mi_set_import,
+            mi_set_export,
+            dlg_set_import,
+            dlg_set_export,
+            mi_sort_offset,
+            mi_sort_name,
+            mi_sort_label,
             tb_btn_add_tz,
+            tb_btn_undo_remove,
             tb_btn_sel_cal,
             tb_btn_sel_col,
             tb_btn_sel_now,
+            tb_btn_live,
+            tb_btn_paste,
+            tb_btn_copy,
+            tb_btn_invite,
+            dlg_invite,
+            txt_invite_summary,
+            spn_invite_duration,
+            mi_copy_plain,
+            mi_copy_markdown,
+            mi_copy_slack,
+            mi_copy_iso,
+            dlg_zone_choice,
+            lbl_zone_choice,
+            cmb_zone_choice,
+            pb_zone_choice_ok,
+            pb_zone_choice_cancel,
+            tb_btn_sort,
             dlg_calendar,
             cal_date,
             pb_dlg_cal_ok,
@@ -245,26 +641,36 @@ impl Widget for Win {
             dlg_col_col_workday,
             pb_dlg_col_ok,
             pb_dlg_col_cancel, 
+            tb_btn_find_meeting,
+            dlg_meeting,
+            spn_meet_duration,
+            spn_meet_days,
+            lst_meeting_results,
+            pb_dlg_meet_find,
+            pb_dlg_meet_close,
         };
 
         Win {
             model,
             widgets,
             config,
+            config_path,
+            // Saving is also turned off when it would overwrite the only copy of settings that could not be read
+            read_only: config_options.read_only || !can_save,
         }
     }
 
     fn init_view(&mut self) {
-        if self.config.zones.len() > 0 {
-            for i in 1..self.config.zones.len() {
-                if let Some(tz_location) = self.config.zones[i].clone() {
-                    if tz_location.len() > 0 {
-                        self.add_tz_selector(tz_location);
-                    }
-                }
+        let (zone_entries, zone_warnings) = self.config.zone_entries();
+        if zone_entries.len() > 0 {
+            for entry in zone_entries.into_iter().skip(1) {
+                self.add_tz_selector(Some(entry));
             }
             self.model.local_relm.stream().emit(Msg::SetToNow);
         }
+        self.show_zone_warnings(&zone_warnings);
+        self.update_zone_set_list();
+        self.schedule_clock_tick();
 
         self.widgets.tb_btn_sel_cal.set_label(Some(format!("{}", self.model.for_date.format("On %Y/%m/%d")).as_ref()));
 
@@ -275,6 +681,9 @@ impl Widget for Win {
         style_context.add_provider(&provider, STYLE_PROVIDER_PRIORITY_APPLICATION);
 
         self.widgets.tz_box.set_border_width(3);
+        self.model.shared_draw_handler.init(&self.widgets.draw_shared);
+        self.update_overlap();
+        self.update_labels();
         
 
     }
@@ -302,7 +711,7 @@ impl WidgetTest for Win {
 
 
 impl Win {
-    fn add_tz_selector(&mut self, tz_location: String) {
+    fn add_tz_selector(&mut self, entry: Option<ZoneEntry>) {
         let midday_colour = (
             self.config.midday_colour.0, 
             self.config.midday_colour.1,
@@ -317,14 +726,462 @@ impl Win {
             self.config.workday_colour.3
         );
 
-        let new_selector = self.widgets.tz_box.add_widget::<TzSelector>((self.model.tz_ctrls.len() as i32, self.model.base_tz.clone(), Some(tz_location.clone()), self.model.for_date.clone(), midday_colour, workday_colour));
+        let row_id = self.model.next_row_id;
+        self.model.next_row_id += 1;
+
+        // The first row is the base when rows are being rebuilt for another zone set
+        let is_base = self.model.tz_ctrls.is_empty();
+        let new_selector = self.widgets.tz_box.add_widget::<TzSelector>((row_id, is_base, self.model.base_tz, entry.clone(), self.model.for_date.clone(), midday_colour, workday_colour, self.config.slot_minutes, self.config.dst_warning_weeks));
         connect!(new_selector@crate::tzselector::Msg::NotifyParentTimeSelectChanged(new_time), self.model.local_relm, Msg::TimeSelectChanged(new_time));
-        connect!(new_selector@crate::tzselector::Msg::NotifyParentTzSelectorRemoveClicked(remove_index), self.model.local_relm, Msg::TimezoneRemove(remove_index));
-        connect!(new_selector@crate::tzselector::Msg::NotifyParentTimezoneSelectChanged(index, ref new_zone), self.model.local_relm, Msg::TimezoneSelectChanged(index, new_zone.clone()));
-        
+        connect!(new_selector@crate::tzselector::Msg::NotifyParentDateTimeSelected(instant), self.model.local_relm, Msg::DateTimeSelected(instant));
+        connect!(new_selector@crate::tzselector::Msg::NotifyParentTzSelectorRemoveClicked(row_id), self.model.local_relm, Msg::TimezoneRemove(row_id));
+        connect!(new_selector@crate::tzselector::Msg::NotifyParentTimezoneSelectChanged(row_id, ref new_zone), self.model.local_relm, Msg::TimezoneSelectChanged(row_id, new_zone.clone()));
+        connect!(new_selector@crate::tzselector::Msg::NotifyParentWorkHoursChanged(row_id, ref work_hours), self.model.local_relm, Msg::WorkHoursChanged(row_id, work_hours.clone()));
+        connect!(new_selector@crate::tzselector::Msg::NotifyParentCalendarChanged(row_id, ref calendar), self.model.local_relm, Msg::CalendarChanged(row_id, calendar.clone()));
+        connect!(new_selector@crate::tzselector::Msg::NotifyParentLabelChanged(row_id, ref label, show_badge), self.model.local_relm, Msg::LabelChanged(row_id, label.clone(), show_badge));
+        connect!(new_selector@crate::tzselector::Msg::NotifyParentRowMoved(from_row_id, to_row_id), self.model.local_relm, Msg::RowMoved(from_row_id, to_row_id));
+        // Any row can be moved up to become the base
+        connect!(new_selector@crate::tzselector::Msg::NotifyParentBaseTzChanged(new_zone), self.model.local_relm, Msg::ChangeBaseTimezone(Some(new_zone)));
+
         self.model.tz_ctrls.push(new_selector);
-        self.model.tz_zones.push(Some(tz_location));
-        
+        self.model.tz_zones.push(entry);
+        self.model.tz_row_ids.push(row_id);
+        self.update_overlap();
+        self.update_labels();
+    }
+
+    // Keeps the active zone set in step with the rows shown
+    fn store_active_set(&mut self) {
+        if let Some(index) = self.config.active_set_index() {
+            self.config.zone_sets[index].zones = self.model.tz_zones.iter().flatten().map(SavedZone::from).collect();
+        }
+    }
+
+    // Replaces every row with those of the zone set called set_name
+    fn show_zone_set(&mut self, set_name: &str) {
+        self.store_active_set();
+        self.config.active_set = set_name.to_string();
+
+        for tz_ctrl in self.model.tz_ctrls.drain(..) {
+            self.widgets.tz_box.remove::<Box>(tz_ctrl.widget());
+        }
+        self.model.tz_zones.clear();
+        self.model.tz_row_ids.clear();
+        // A row removed from another set cannot be put back into this one
+        self.model.removed_row = None;
+        self.widgets.tb_btn_undo_remove.set_sensitive(false);
+
+        let (zone_entries, zone_warnings) = self.config.zone_entries();
+        self.model.base_tz = zone_entries.first().map(|entry| entry.tz);
+        if zone_entries.is_empty() {
+            self.add_tz_selector(None);
+        } else {
+            for entry in zone_entries {
+                self.add_tz_selector(Some(entry));
+            }
+            self.model.local_relm.stream().emit(Msg::SetToNow);
+        }
+        self.show_zone_warnings(&zone_warnings);
+        self.update_zone_set_list();
+        self.queue_save();
+    }
+
+    // Creates, renames or duplicates the active zone set as the set name dialog was opened for
+    fn apply_zone_set_name(&mut self, name: &str) {
+        let active_index = match self.config.active_set_index() {
+            Some(index) => index,
+            None => return,
+        };
+        let set_name = {
+            // A set can be renamed to itself, say to change its case
+            let taken_names: Vec<&str> = self.config.zone_sets.iter().enumerate()
+                .filter(|(i, _)| self.model.set_name_edit != ZoneSetEdit::Rename || *i != active_index)
+                .map(|(_, set)| set.name.as_str())
+                .collect();
+            unique_set_name(name, &taken_names)
+        };
+
+        match self.model.set_name_edit {
+            ZoneSetEdit::Create => {
+                self.config.zone_sets.push(ZoneSet::new(&set_name));
+                self.show_zone_set(&set_name);
+            },
+            ZoneSetEdit::Rename => {
+                self.config.zone_sets[active_index].name = set_name.clone();
+                self.config.active_set = set_name;
+                self.update_zone_set_list();
+                self.queue_save();
+            },
+            ZoneSetEdit::Duplicate => {
+                self.store_active_set();
+                let zones = self.config.zone_sets[active_index].zones.clone();
+                self.config.zone_sets.insert(active_index + 1, ZoneSet { name: set_name.clone(), zones });
+                self.show_zone_set(&set_name);
+            },
+        }
+    }
+
+    // Adds the zone sets in the file at path alongside the existing ones and shows the one the
+    // file had active
+    fn import_zone_sets(&mut self, path: PathBuf) {
+        let (zone_sets, file_active_set) = match import_zone_sets(&path) {
+            Ok(imported) => imported,
+            Err(err) => {
+                self.show_status("zone_file", &format!("Could not import zone sets: {}", err));
+                return;
+            },
+        };
+
+        let mut show_name = None;
+        let imported_count = zone_sets.len();
+        for mut zone_set in zone_sets {
+            // Sets already here are kept, an imported set with the same name gets a number
+            let taken_names: Vec<&str> = self.config.zone_sets.iter().map(|set| set.name.as_str()).collect();
+            let set_name = unique_set_name(&zone_set.name, &taken_names);
+            if zone_set.name == file_active_set || show_name.is_none() {
+                show_name = Some(set_name.clone());
+            }
+            zone_set.name = set_name;
+            self.config.zone_sets.push(zone_set);
+        }
+
+        if let Some(set_name) = show_name {
+            self.show_zone_set(&set_name);
+        }
+        self.show_status("zone_file", &format!("Imported {} zone set(s) from {}", imported_count, path.display()));
+    }
+
+    // Writes the active zone set to path, as TOML unless the name ends in .json or .ics
+    fn export_zone_set(&mut self, mut path: PathBuf) {
+        if ZoneFileFormat::from_path(&path).is_none() {
+            let mut file_name = path.into_os_string();
+            file_name.push(".toml");
+            path = PathBuf::from(file_name);
+        }
+        let zone_set = match self.config.active_set_index() {
+            Some(index) => &self.config.zone_sets[index],
+            None => return,
+        };
+
+        match export_zone_set(&path, zone_set) {
+            Ok(_) => self.show_status("zone_file", &format!("Exported \"{}\" to {}", zone_set.name, path.display())),
+            Err(err) => self.show_status("zone_file", &format!("Could not export zone set: {}", err)),
+        }
+    }
+
+    fn show_status(&self, context: &str, text: &str) {
+        let context_id = self.widgets.sb_status.get_context_id(context);
+        self.widgets.sb_status.remove_all(context_id);
+        self.widgets.sb_status.push(context_id, text);
+    }
+
+    // Lists the zone sets in the header bar with the active one selected
+    fn update_zone_set_list(&self) {
+        self.widgets.cmb_zone_set.remove_all();
+        for zone_set in self.config.zone_sets.iter() {
+            self.widgets.cmb_zone_set.append_text(&zone_set.name);
+        }
+        self.widgets.cmb_zone_set.set_active(self.config.active_set_index().map(|index| index as u32));
+        self.widgets.mi_set_delete.set_sensitive(self.config.zone_sets.len() > 1);
+    }
+
+    // Zones in the config that could not be read are left out rather than stopping start up
+    fn show_zone_warnings(&self, zone_warnings: &[String]) {
+        let context_id = self.widgets.sb_status.get_context_id("config");
+        self.widgets.sb_status.remove_all(context_id);
+        if !zone_warnings.is_empty() {
+            self.widgets.sb_status.push(context_id, &format!("Some saved timezones were skipped: {}", zone_warnings.join(", ")));
+        }
+    }
+
+    // The time the sliders are at, in the base timezone
+    fn selected_instant(&self) -> Option<DateTime<Tz>> {
+        let day_start = start_of_day(self.model.base_tz?, self.model.for_date);
+        Some(day_start + Duration::minutes(self.model.selected_slot as i64 * self.config.slot_minutes as i64))
+    }
+
+    // Writes an invite for a meeting starting at the selected time, adding .ics to the name when
+    // it has no extension
+    fn export_invite(&self, mut path: PathBuf) {
+        let start = match self.selected_instant() {
+            Some(start) => start,
+            None => return,
+        };
+        if path.extension().is_none() {
+            path.set_extension("ics");
+        }
+        let invite = Invite {
+            summary: self.widgets.txt_invite_summary.get_text().to_string(),
+            start,
+            duration: Duration::minutes(self.widgets.spn_invite_duration.get_value_as_int() as i64),
+            rows: self.model.tz_zones.iter().flatten().map(|entry| (entry.display_name(), entry.tz)).collect(),
+        };
+
+        match std::fs::write(&path, invite_to_ics(&invite, Utc::now())) {
+            Ok(_) => self.show_status("invite", &format!("Saved an invite for {} to {}", start.format("%a %Y/%m/%d %I:%M %P %Z"), path.display())),
+            Err(err) => self.show_status("invite", &format!("Could not save the invite: {}", err)),
+        }
+    }
+
+    // Puts the time the sliders are at on the clipboard, as the local time of every row with a
+    // timezone
+    fn copy_selected_time(&self, format: CopyFormat) {
+        let instant = match self.selected_instant() {
+            Some(instant) => instant,
+            None => {
+                self.show_status("copy", "Choose a base timezone before copying the time");
+                return;
+            },
+        };
+        let rows: Vec<(String, DateTime<Tz>)> = self.model.tz_zones.iter().flatten()
+            .map(|entry| (entry.display_name(), instant.with_timezone(&entry.tz)))
+            .collect();
+
+        Clipboard::get(&SELECTION_CLIPBOARD).set_text(&format_times(format, &rows));
+        self.show_status("copy", &format!("Copied {} as {}", instant.format("%a %Y/%m/%d %I:%M %P %Z"), format.name()));
+    }
+
+    // Goes to a pasted time, first asking which timezone an abbreviation such as IST stands for
+    // rather than guessing
+    fn paste_time_entry(&mut self, entry: TimeEntry) {
+        if let Some(EntryZone::Abbreviation(abbreviation)) = entry.zone.as_ref() {
+            let meanings = abbreviation_meanings(abbreviation);
+            if meanings.len() > 1 {
+                self.widgets.lbl_zone_choice.set_text(&format!("Which timezone does {} stand for?", abbreviation));
+                self.widgets.cmb_zone_choice.remove_all();
+                for meaning in meanings.iter() {
+                    self.widgets.cmb_zone_choice.append_text(&format!("{} ({})", meaning.name, format_utc_offset(meaning.utc_offset_minutes)));
+                }
+                self.widgets.cmb_zone_choice.set_active(Some(0));
+                self.model.pending_paste = Some(entry);
+                self.widgets.dlg_zone_choice.show_all();
+                return;
+            }
+        }
+
+        let base_tz = match self.model.base_tz {
+            Some(base_tz) => base_tz,
+            None => {
+                self.show_status("paste", "Choose a base timezone before pasting a time");
+                return;
+            },
+        };
+        // A day or time with no date is taken from now, as it would be in a message
+        match entry.instant(Utc::now().with_timezone(&base_tz)) {
+            Ok(instant) => {
+                self.show_status("paste", &format!("Pasted {}", instant.format("%a %Y/%m/%d %I:%M %P %Z")));
+                self.model.local_relm.stream().emit(Msg::DateTimeSelected(instant.with_timezone(&Utc)));
+            },
+            Err(err) => self.show_status("paste", &format!("Could not paste a time, {}", err)),
+        }
+    }
+
+    // Turning the toggle off sends LiveToggled(false)
+    fn stop_live(&self) {
+        self.widgets.tb_btn_live.set_active(false);
+    }
+
+    // Slots are rounded from whole minutes, so the slot live mode shows can only change as a minute
+    // starts. The next tick comes just after that or after CLOCK_TICK_MS, whichever is sooner
+    fn schedule_clock_tick(&self) {
+        let into_minute = Utc::now().timestamp_millis().rem_euclid(60_000);
+        let delay = (60_000 - into_minute + 50).min(CLOCK_TICK_MS as i64);
+        relm::timeout(self.model.local_relm.stream(), delay as u32, || Msg::ClockTick);
+    }
+
+    // Moves on to the next day once midnight passes in the base timezone, then moves the sliders to
+    // now each time now reaches another slot. A slider moved by hand stays put until then
+    fn follow_clock(&mut self) {
+        let now = Utc::now();
+        let today = match self.model.base_tz {
+            Some(base_tz) => now.with_timezone(&base_tz).naive_local().date(),
+            None => Local::now().naive_local().date(),
+        };
+        if today != self.model.for_date {
+            self.set_for_date(today);
+            self.model.live_slot = None;
+        }
+
+        if let Some(base_tz) = self.model.base_tz {
+            // The slot shown is the one decided on, rounded as the Now button rounds
+            let index = index_from_time(base_tz, base_tz, self.model.for_date, now.with_timezone(&base_tz).time(), self.config.slot_minutes);
+            if self.model.live_slot != Some(index as i64) {
+                self.model.live_slot = Some(index as i64);
+                self.model.local_relm.stream().emit(Msg::TimeSelectChanged(index));
+            }
+        }
+    }
+
+    fn set_for_date(&mut self, for_date: NaiveDate) {
+        self.model.for_date = for_date;
+        self.widgets.tb_btn_sel_cal.set_label(Some(format!("{}", self.model.for_date.format("On %Y/%m/%d")).as_ref()));
+        for i in 0..self.model.tz_ctrls.len() {
+            self.model.tz_ctrls[i].emit(crate::tzselector::Msg::FromParentDateChanged(self.model.for_date));
+        };
+        self.update_overlap();
+        self.queue_save();
+    }
+
+    // Works out the part of the day all rows are working, drawn across every row, and sends each row
+    // where it alone is blocking that
+    fn update_overlap(&mut self) {
+        let rows: Vec<(usize, Tz, WorkHours)> = self.model.tz_zones.iter().enumerate()
+            .filter_map(|(i, entry)| entry.as_ref().map(|entry| (i, entry.tz, entry.work_hours.clone())))
+            .collect();
+
+        self.model.shared = vec![];
+        let mut blocking = vec![vec![]; self.model.tz_ctrls.len()];
+        // A single row would only repeat its own working hours
+        if let (Some(base_tz), true) = (self.model.base_tz, rows.len() > 1) {
+            let zones: Vec<(Tz, WorkHours)> = rows.iter().map(|(_, tz, work_hours)| (*tz, work_hours.clone())).collect();
+            let overlap = day_overlap(base_tz, &zones, self.model.for_date, self.config.slot_minutes);
+            self.model.shared = overlap.shared;
+            for ((i, _, _), row_blocking) in rows.iter().zip(overlap.blocking.into_iter()) {
+                blocking[*i] = row_blocking;
+            }
+        }
+
+        for (i, row_blocking) in blocking.into_iter().enumerate() {
+            self.model.tz_ctrls[i].emit(crate::tzselector::Msg::FromParentBlockingChanged(row_blocking));
+        }
+        self.widgets.draw_shared.queue_draw();
+    }
+
+    // One band down the whole stack of rows, over the labels and entries as well as the day bars.
+    // The day bars of every row start and end in the same place, so the first one gives the width
+    fn draw_shared(&mut self) {
+        let ctx = self.model.shared_draw_handler.get_context();
+        let day_bar = match self.model.tz_ctrls.first().and_then(|tz_ctrl| find_day_bar(tz_ctrl.widget().upcast_ref())) {
+            Some(day_bar) => day_bar,
+            None => return,
+        };
+        let x = match day_bar.translate_coordinates(&self.widgets.draw_shared, 0, 0) {
+            Some((x, _)) => x as f64,
+            None => return,
+        };
+        let w = day_bar.get_allocated_width() as f64;
+        let h = self.widgets.draw_shared.get_allocated_height() as f64;
+
+        ctx.set_source_rgba(0.2, 0.8, 0.2, 0.2);
+        for (from, to) in self.model.shared.iter() {
+            ctx.rectangle(x + from * w, 0.0, (to - from) * w, h);
+        }
+        ctx.fill();
+    }
+
+    fn row_position(&self, row_id: RowId) -> Option<usize> {
+        self.model.tz_row_ids.iter().position(|id| *id == row_id)
+    }
+
+    fn move_row(&mut self, from_index: usize, to_index: usize) {
+        let row_count = self.model.tz_ctrls.len();
+        if from_index == to_index || from_index >= row_count || to_index >= row_count {
+            return;
+        }
+
+        let tz_ctrl = self.model.tz_ctrls.remove(from_index);
+        self.model.tz_ctrls.insert(to_index, tz_ctrl);
+        let entry = self.model.tz_zones.remove(from_index);
+        self.model.tz_zones.insert(to_index, entry);
+        let row_id = self.model.tz_row_ids.remove(from_index);
+        self.model.tz_row_ids.insert(to_index, row_id);
+
+        // The base row has to have a timezone, put things back if the move would leave it without one
+        if self.model.tz_zones[0].is_none() {
+            let tz_ctrl = self.model.tz_ctrls.remove(to_index);
+            self.model.tz_ctrls.insert(from_index, tz_ctrl);
+            let entry = self.model.tz_zones.remove(to_index);
+            self.model.tz_zones.insert(from_index, entry);
+            let row_id = self.model.tz_row_ids.remove(to_index);
+            self.model.tz_row_ids.insert(from_index, row_id);
+            return;
+        }
+
+        self.reorder_rows();
+    }
+
+    // Sorts the rows below the base, rows without a timezone go last
+    fn sort_rows(&mut self, sort: ZoneSort) {
+        let instant = match self.model.base_tz {
+            Some(base_tz) => start_of_day(base_tz, self.model.for_date).with_timezone(&Utc),
+            None => Utc::now(),
+        };
+
+        let mut rows: Vec<(Component<TzSelector>, Option<ZoneEntry>, RowId)> = self.model.tz_ctrls.drain(1..)
+            .zip(self.model.tz_zones.drain(1..))
+            .zip(self.model.tz_row_ids.drain(1..))
+            .map(|((tz_ctrl, entry), row_id)| (tz_ctrl, entry, row_id))
+            .collect();
+        rows.sort_by(|(_, a, _), (_, b, _)| match (a, b) {
+            (Some(a), Some(b)) => compare_entries(a, b, sort, instant),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+        for (tz_ctrl, entry, row_id) in rows {
+            self.model.tz_ctrls.push(tz_ctrl);
+            self.model.tz_zones.push(entry);
+            self.model.tz_row_ids.push(row_id);
+        }
+
+        self.reorder_rows();
+    }
+
+    // Puts the widgets in tz_box into model order and tells each row where it now is
+    fn reorder_rows(&mut self) {
+        for (i, tz_ctrl) in self.model.tz_ctrls.iter().enumerate() {
+            tz_ctrl.emit(crate::tzselector::Msg::FromParentBaseChanged(i == 0));
+            self.widgets.tz_box.reorder_child(tz_ctrl.widget(), i as i32);
+        }
+        self.queue_save();
+
+        let base_tz = self.model.tz_zones[0].as_ref().map(|entry| entry.tz);
+        if base_tz != self.model.base_tz {
+            self.model.local_relm.stream().emit(Msg::ChangeBaseTimezone(base_tz));
+        }
+        self.update_overlap();
+    }
+
+    // Lets every row's timezone search find rows by their label
+    fn update_labels(&self) {
+        let labels: Vec<(Tz, String)> = self.model.tz_zones.iter().flatten()
+            .filter_map(|entry| Some((entry.tz, entry.label.clone()?)))
+            .collect();
+        for tz_ctrl in self.model.tz_ctrls.iter() {
+            tz_ctrl.emit(crate::tzselector::Msg::FromParentLabelsChanged(labels.clone()));
+        }
+    }
+
+    // Lists the times on the selected date (and following days) when every timezone is working
+    fn search_meetings(&mut self) {
+        let base_tz: Tz = match self.model.base_tz {
+            Some(tz) => tz,
+            None => return,
+        };
+        let zones: Vec<(Tz, WorkHours)> = self.model.tz_zones.iter().flatten()
+            .map(|entry| (entry.tz, entry.work_hours.clone()))
+            .collect();
+        let duration = Duration::minutes(self.widgets.spn_meet_duration.get_value_as_int() as i64);
+        let extra_days = self.widgets.spn_meet_days.get_value_as_int() as u32;
+
+        self.model.meeting_windows = find_meeting_windows(base_tz, &zones, self.model.for_date, extra_days, duration, self.config.slot_minutes);
+
+        for row in self.widgets.lst_meeting_results.get_children() {
+            self.widgets.lst_meeting_results.remove(&row);
+        }
+        for window in self.model.meeting_windows.iter() {
+            let window_text = format!("{} - {} {}    {} together, {:.0}% of the shortest working day",
+                window.start.format("%a %d %b %I:%M %P"),
+                window.end.format("%I:%M %P"),
+                window.start.format("%Z"),
+                format_minutes(window.minutes() as i32).trim_start_matches('+'),
+                window.score * 100.0);
+            self.widgets.lst_meeting_results.insert(&Label::new(Some(window_text.as_str())), -1);
+        }
+        if self.model.meeting_windows.is_empty() {
+            self.widgets.lst_meeting_results.insert(&Label::new(Some("No times found when every timezone is working")), -1);
+        }
+        self.widgets.lst_meeting_results.show_all();
     }
 
     fn save_config(&mut self) {
@@ -335,27 +1192,63 @@ impl Win {
         self.config.win_width = w;
         self.config.win_height = h;
 
-        match confy::store("TimezoneRS", &self.config) {
-            Ok(_) => {},
-            Err(_) => {},
+        self.store_active_set();
+
+        let config_path = match self.config_path.as_ref() {
+            Some(config_path) if !self.read_only => config_path,
+            _ => return,
+        };
+        match save_config(config_path, &self.config) {
+            Ok(_) => self.widgets.ib_save_error.hide(),
+            // Shown above the status bar without getting in the way, the next change tries again
+            Err(err) => {
+                self.widgets.lbl_save_error.set_text(&err);
+                self.widgets.ib_save_error.show();
+            },
         }
     }
 
+    // Saves once there have been no more changes for SAVE_DELAY_MS
+    fn queue_save(&mut self) {
+        self.model.save_generation = self.model.save_generation.wrapping_add(1);
+        let save_generation = self.model.save_generation;
+        relm::timeout(self.model.local_relm.stream(), SAVE_DELAY_MS, move || Msg::SaveConfig(save_generation));
+    }
+
+}
+
+// The day bar of a row, rows only hand back their outer box
+fn find_day_bar(widget: &gtk::Widget) -> Option<DrawingArea> {
+    match widget.clone().downcast::<DrawingArea>() {
+        Ok(day_bar) => Some(day_bar),
+        Err(widget) => widget.downcast::<Container>().ok()?.get_children().iter().find_map(find_day_bar),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use gtk::{ToolButtonExt, ContainerExt, Box, Entry, Label, LabelExt, EntryExt};
+    use gtk::{ToolButtonExt, ContainerExt, BinExt, Box, Entry, Label, LabelExt, EntryExt, Overlay};
     use gtk_test::{assert_label, assert_text};
     use relm_test::{Observer, click, relm_observer_new, relm_observer_wait, enter_key, key_press, key_release, enter_keys};
     use relm::{Cast};
     use gdk::keys::constants as key;
 
     use crate::win::Win;
+    use crate::config::ConfigOptions;
+
+    // Settings from a file that is not there and never written, so running the tests cannot touch
+    // the real settings
+    fn test_config_options() -> ConfigOptions {
+        ConfigOptions {
+            path: Some(std::env::temp_dir().join(format!("timezoners-test-{}.toml", std::process::id()))),
+            read_only: true,
+            ..ConfigOptions::default()
+        }
+    }
 
     #[test]
     fn main_window_created() {
-        let (_component, _, widgets) = relm::init_test::<Win>(()).expect("init_test failed");
+        let (_component, _, widgets) = relm::init_test::<Win>(test_config_options()).expect("init_test failed");
         let tb_btn_add_tz = &widgets.tb_btn_add_tz;
         let tb_btn_sel_cal = &widgets.tb_btn_sel_cal;
         let tb_btn_sel_col = &widgets.tb_btn_sel_col;
@@ -371,7 +1264,7 @@ mod tests {
 
     #[test]
     fn create_tz_selector() {
-        let (component, _, widgets) = relm::init_test::<Win>(()).expect("init_test failed");
+        let (component, _, widgets) = relm::init_test::<Win>(test_config_options()).expect("init_test failed");
 
         let tb_btn_add_tz = &widgets.tb_btn_add_tz;
 
@@ -386,14 +1279,15 @@ mod tests {
 
     #[test]
     fn enter_times() {
-        let (component, _,  widgets) = relm::init_test::<Win>(()).expect("init_test failed");
+        let (component, _,  widgets) = relm::init_test::<Win>(test_config_options()).expect("init_test failed");
 
         let tz_selector_main = component.widget();
         let window_box = tz_selector_main.get_children();
         
         let main_box = window_box.get(0).unwrap().clone().downcast::<Box>().expect("Could not get the main box");
         let kids = main_box.get_children();
-        let widgets_box = kids.get(1).unwrap().clone().downcast::<Box>().expect("Could not get widgets box");
+        let widgets_overlay = kids.get(1).unwrap().clone().downcast::<Overlay>().expect("Could not get widgets overlay");
+        let widgets_box = widgets_overlay.get_child().unwrap().downcast::<Box>().expect("Could not get widgets box");
         let kids = widgets_box.get_children();
         let tz_box = kids.get(0).unwrap().clone().downcast::<Box>().expect("Could not get first tz selector box");
         let kids = tz_box.get_children();
@@ -423,6 +1317,7 @@ mod tests {
         enter_key(&time_entry, key::KP_0);
         assert_text!(time_entry, "11:0");
         enter_key(&time_entry, key::KP_0);
+        enter_key(&time_entry, key::Return);
         
         assert_text!(time_label, "11:00 am");
 
@@ -438,6 +1333,7 @@ mod tests {
         enter_key(&time_entry, key::KP_0);
         assert_text!(time_entry, "11:0");
         enter_key(&time_entry, key::KP_6);
+        enter_key(&time_entry, key::Return);
         
         assert_text!(time_label, "11:00 am");
 
@@ -453,6 +1349,7 @@ mod tests {
         enter_key(&time_entry, key::KP_0);
         assert_text!(time_entry, "11:0");
         enter_key(&time_entry, key::KP_9);
+        enter_key(&time_entry, key::Return);
         
         assert_text!(time_label, "11:15 am");
 
@@ -466,21 +1363,25 @@ mod tests {
         enter_key(&time_entry, key::space);
         assert_text!(time_entry, "a1l ");
         enter_key(&time_entry, key::KP_9);
-        
-        assert_text!(time_label, "12:00 am");
+        enter_key(&time_entry, key::Return);
+
+        // Not understood, so left in the entry to be corrected and the time stays put
+        assert_text!(time_entry, "a1l 9");
+        assert_text!(time_label, "11:15 am");
         
     }
 
     #[test]
     fn enter_times_keys() {
-        let (component, _,  widgets) = relm::init_test::<Win>(()).expect("init_test failed");
+        let (component, _,  widgets) = relm::init_test::<Win>(test_config_options()).expect("init_test failed");
 
         let tz_selector_main = component.widget();
         let window_box = tz_selector_main.get_children();
         
         let main_box = window_box.get(0).unwrap().clone().downcast::<Box>().expect("Could not get the main box");
         let kids = main_box.get_children();
-        let widgets_box = kids.get(1).unwrap().clone().downcast::<Box>().expect("Could not get widgets box");
+        let widgets_overlay = kids.get(1).unwrap().clone().downcast::<Overlay>().expect("Could not get widgets overlay");
+        let widgets_box = widgets_overlay.get_child().unwrap().downcast::<Box>().expect("Could not get widgets box");
         let kids = widgets_box.get_children();
         let tz_box = kids.get(0).unwrap().clone().downcast::<Box>().expect("Could not get first tz selector box");
         let kids = tz_box.get_children();
@@ -500,16 +1401,29 @@ mod tests {
         let time_entry = kids.get(1).unwrap().clone().downcast::<Entry>().expect("Could not get time entry");
         
         enter_keys(&time_entry, "11:00");
+        enter_key(&time_entry, key::Return);
         assert_text!(time_label, "11:00 am");
 
         enter_keys(&time_entry, "11:06");
+        enter_key(&time_entry, key::Return);
         assert_text!(time_label, "11:00 am");
 
         enter_keys(&time_entry, "11:09");
+        enter_key(&time_entry, key::Return);
         assert_text!(time_label, "11:15 am");
 
+        enter_keys(&time_entry, "9pm");
+        enter_key(&time_entry, key::Return);
+        assert_text!(time_label, "09:00 pm");
+
+        enter_keys(&time_entry, "-1h30m");
+        enter_key(&time_entry, key::Return);
+        assert_text!(time_label, "07:30 pm");
+
         enter_keys(&time_entry, "1a1aa");
-        assert_text!(time_label, "12:00 am");
+        enter_key(&time_entry, key::Return);
+        assert_text!(time_entry, "1a1aa");
+        assert_text!(time_label, "07:30 pm");
         
     }
 }
\ No newline at end of file
@@ -0,0 +1,83 @@
+use std::path::Path;
+use chrono::{Datelike, Local};
+use serde::Serialize;
+use timezoners_core::{ZoneSet, zone_set_from_ics, zone_set_to_ics};
+use crate::config::{CONFIG_VERSION, config_from_value};
+
+// File formats zone sets are shared in, picked by file extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ZoneFileFormat {
+    Json,
+    Toml,
+    Ics,
+}
+
+impl ZoneFileFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(ZoneFileFormat::Json),
+            "toml" => Some(ZoneFileFormat::Toml),
+            "ics" | "ical" => Some(ZoneFileFormat::Ics),
+            _ => None,
+        }
+    }
+}
+
+// The zone set part of Config, so an exported file reads like a config file and a config file
+// can be imported
+#[derive(Serialize)]
+struct ZoneSetFile<'a> {
+    version: i64,
+    active_set: &'a str,
+    zone_sets: &'a [ZoneSet],
+}
+
+// Writes zone_set to path in the format its extension asks for
+pub fn export_zone_set(path: &Path, zone_set: &ZoneSet) -> Result<(), String> {
+    let format = ZoneFileFormat::from_path(path)
+        .ok_or_else(|| format!("{} does not end in .json, .toml or .ics", path.display()))?;
+    let zone_set_file = ZoneSetFile {
+        version: CONFIG_VERSION,
+        active_set: &zone_set.name,
+        zone_sets: std::slice::from_ref(zone_set),
+    };
+
+    let text = match format {
+        ZoneFileFormat::Json => serde_json::to_string_pretty(&zone_set_file).map_err(|e| e.to_string())?,
+        ZoneFileFormat::Toml => toml::to_string(&zone_set_file).map_err(|e| e.to_string())?,
+        // Covers meetings booked into next year as well
+        ZoneFileFormat::Ics => {
+            let year = Local::now().year();
+            zone_set_to_ics(zone_set, year..=year + 1)
+        },
+    };
+
+    std::fs::write(path, text).map_err(|e| format!("could not write {}: {}", path.display(), e))
+}
+
+// The zone sets in the file at path, along with the name of the set it had active
+pub fn import_zone_sets(path: &Path) -> Result<(Vec<ZoneSet>, String), String> {
+    let format = ZoneFileFormat::from_path(path)
+        .ok_or_else(|| format!("{} does not end in .json, .toml or .ics", path.display()))?;
+    let text = std::fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+
+    if format == ZoneFileFormat::Ics {
+        let file_name = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        let zone_set = zone_set_from_ics(&text, &file_name)?;
+        let active_set = zone_set.name.clone();
+        return Ok((vec![zone_set], active_set));
+    }
+
+    // Read the same way as settings saved by an earlier version
+    let value: toml::Value = match format {
+        ZoneFileFormat::Json => serde_json::from_str(&text).map_err(|e| e.to_string())?,
+        _ => toml::from_str(&text).map_err(|e| e.to_string())?,
+    };
+    if value.get("zone_sets").is_none() && value.get("zones").is_none() {
+        return Err(format!("no zone sets found in {}", path.display()));
+    }
+    let config = config_from_value(value)?;
+
+    Ok((config.zone_sets, config.active_set))
+}
@@ -0,0 +1,429 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+
+use crate::{ZoneDay, parse_property, resolve_local, start_of_day, unescape_text, unfold_lines};
+
+const WEEKDAYS: [(Weekday, &str); 7] = [
+    (Weekday::Mon, "MO"), (Weekday::Tue, "TU"), (Weekday::Wed, "WE"), (Weekday::Thu, "TH"),
+    (Weekday::Fri, "FR"), (Weekday::Sat, "SA"), (Weekday::Sun, "SU"),
+];
+
+/// An event read from a calendar, when its time is taken up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub summary: String,
+    /// Start of the first occurrence, in the timezone the event was written in.
+    pub start: DateTime<Tz>,
+    pub duration: Duration,
+    pub repeat: Option<Repeat>,
+    /// Starts of occurrences left out, removed by an EXDATE or replaced by an event of their own.
+    pub exceptions: Vec<DateTime<Utc>>,
+}
+
+/// How often an event repeats. Only daily and weekly rules are understood, events repeating by
+/// other rules are read as happening once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repeat {
+    /// Days between occurrences, 7 for every week.
+    pub interval_days: i64,
+    /// Days of the week it falls on, empty for daily rules.
+    pub weekdays: Vec<Weekday>,
+    pub count: Option<u32>,
+    /// Start of the last occurrence there can be, the end of the day for an UNTIL with no time.
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl CalendarEvent {
+    /// Start and end of each occurrence that overlaps `from` - `to`.
+    pub fn occurrences(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let overlapping = |start: DateTime<Utc>| start < to && start + self.duration > from;
+        let repeat = match self.repeat.as_ref() {
+            Some(repeat) => repeat,
+            None => {
+                let start = self.start.with_timezone(&Utc);
+                return if overlapping(start) { vec![(start, start + self.duration)] } else { vec![] };
+            },
+        };
+
+        // Occurrences keep the local time of the first one, across clock changes as well. One the
+        // clocks going forward skips over comes just after the gap rather than not at all
+        let tz = self.start.timezone();
+        let first_date = self.start.naive_local().date();
+        let time = self.start.naive_local().time();
+        // COUNT numbers the occurrences from the first one, without it the walk can start the day
+        // before the earliest occurrence that could still reach `from`
+        let mut date = match repeat.count {
+            Some(_) => first_date,
+            None => first_date.max((from - self.duration).with_timezone(&tz).naive_local().date().pred_opt().unwrap()),
+        };
+        let mut occurrences = vec![];
+        let mut count = 0;
+        while !matches!(repeat.count, Some(max) if count >= max) {
+            let start = resolve_local(tz, date.and_time(time)).0.with_timezone(&Utc);
+            if start >= to || matches!(repeat.until, Some(until) if start > until) {
+                break;
+            }
+            if repeat.falls_on(first_date, date) {
+                count += 1;
+                if overlapping(start) && !self.exceptions.contains(&start) {
+                    occurrences.push((start, start + self.duration));
+                }
+            }
+            date = date.succ_opt().unwrap();
+        }
+        occurrences
+    }
+}
+
+impl Repeat {
+    fn falls_on(&self, first_date: NaiveDate, date: NaiveDate) -> bool {
+        if self.weekdays.is_empty() {
+            return (date - first_date).num_days() % self.interval_days == 0;
+        }
+        // Weekly rules count weeks from the Monday of the first one
+        let first_monday = first_date - Duration::days(first_date.weekday().num_days_from_monday() as i64);
+        self.weekdays.contains(&date.weekday()) && (date - first_monday).num_days() / 7 % (self.interval_days / 7) == 0
+    }
+}
+
+/// Reads the events of every calendar in an iCalendar file. Times without a timezone, and events
+/// taking whole days, are taken to be in `default_tz`. Events marked as free or cancelled are left
+/// out, as are events whose start cannot be read. An event with a RECURRENCE-ID stands in for the
+/// occurrence it names of the repeating event with the same UID.
+pub fn events_from_ics(text: &str, default_tz: Tz) -> Result<Vec<CalendarEvent>, String> {
+    let lines = unfold_lines(text);
+    if !matches!(lines.first(), Some(line) if line.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("not an iCalendar file".to_string());
+    }
+
+    let mut all_lines = vec![];
+    let mut event: Option<EventLines> = None;
+    // Components such as VALARM inside an event have their own properties
+    let mut nested = 0;
+    for line in lines.iter() {
+        let property = match parse_property(line) {
+            Some(property) => property,
+            None => continue,
+        };
+        let value = property.value.trim();
+        match (property.name.as_str(), event.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => event = Some(EventLines::default()),
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => all_lines.extend(event.take()),
+            (_, Some(_)) if nested > 0 => {},
+            ("UID", Some(lines)) => lines.uid = value.to_string(),
+            ("SUMMARY", Some(lines)) => lines.summary = unescape_text(value),
+            ("DTSTART", Some(lines)) => lines.start = parse_date_time(value, property.param("TZID"), default_tz),
+            ("DTEND", Some(lines)) => lines.end = parse_date_time(value, property.param("TZID"), default_tz),
+            ("DURATION", Some(lines)) => lines.duration = parse_duration(value),
+            ("RRULE", Some(lines)) => lines.rule = Some(value.to_string()),
+            ("EXDATE", Some(lines)) => lines.exdates.extend(value.split(',')
+                .filter_map(|exdate| parse_date_time(exdate.trim(), property.param("TZID"), default_tz))),
+            ("RECURRENCE-ID", Some(lines)) => lines.recurrence_id = parse_date_time(value, property.param("TZID"), default_tz),
+            ("TRANSP", Some(lines)) => lines.free = value.eq_ignore_ascii_case("TRANSPARENT"),
+            ("STATUS", Some(lines)) => lines.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {},
+        }
+    }
+
+    // The occurrences replaced are left out of the repeating event, even when what replaces them
+    // is itself left out for being free or cancelled
+    let mut events = vec![];
+    for lines in all_lines.iter() {
+        let mut calendar_event = match lines.to_event() {
+            Some(calendar_event) => calendar_event,
+            None => continue,
+        };
+        if lines.recurrence_id.is_none() && !lines.uid.is_empty() {
+            let start = calendar_event.start;
+            calendar_event.exceptions.extend(all_lines.iter()
+                .filter(|other| other.uid == lines.uid)
+                .filter_map(|other| other.recurrence_id)
+                .map(|recurrence_id| occurrence_start(start, recurrence_id)));
+        }
+        events.push(calendar_event);
+    }
+    Ok(events)
+}
+
+/// Parts of `zone_day` taken up by `events`, as `(from, to)` fractions of the day in time order
+/// with overlapping events joined up.
+pub fn busy_day_spans(events: &[CalendarEvent], zone_day: &ZoneDay) -> Vec<(f64, f64)> {
+    let day_start = zone_day.start.with_timezone(&Utc);
+    let day_end = day_start + Duration::minutes(zone_day.length_minutes);
+    let mut busy: Vec<(DateTime<Utc>, DateTime<Utc>)> = events.iter()
+        .flat_map(|event| event.occurrences(day_start, day_end))
+        .collect();
+    busy.sort();
+
+    let fraction = |instant: DateTime<Utc>| {
+        ((instant - day_start).num_seconds() as f64 / (zone_day.length_minutes * 60) as f64).clamp(0.0, 1.0)
+    };
+    let mut spans: Vec<(f64, f64)> = vec![];
+    for (start, end) in busy {
+        let (from, to) = (fraction(start), fraction(end));
+        match spans.last_mut() {
+            Some(last) if from <= last.1 => last.1 = last.1.max(to),
+            _ => spans.push((from, to)),
+        }
+    }
+    spans
+}
+
+// Properties of one VEVENT as they are read
+#[derive(Default)]
+struct EventLines {
+    uid: String,
+    summary: String,
+    start: Option<(DateTime<Tz>, bool)>,
+    end: Option<(DateTime<Tz>, bool)>,
+    duration: Option<Duration>,
+    rule: Option<String>,
+    exdates: Vec<(DateTime<Tz>, bool)>,
+    recurrence_id: Option<(DateTime<Tz>, bool)>,
+    free: bool,
+    cancelled: bool,
+}
+
+impl EventLines {
+    fn to_event(&self) -> Option<CalendarEvent> {
+        if self.free || self.cancelled {
+            return None;
+        }
+        let (start, all_day) = self.start?;
+        // With neither an end nor a duration an event takes a day, or no time at all
+        let duration = match (self.end, self.duration) {
+            (Some((end, _)), _) => end.signed_duration_since(start),
+            (None, Some(duration)) => duration,
+            (None, None) if all_day => Duration::days(1),
+            (None, None) => Duration::zero(),
+        };
+        // Only the first time of an event repeating by a rule that is not understood is shown
+        let repeat = self.rule.as_deref().and_then(|rule| parse_rule(rule, start.timezone()));
+        let exceptions = self.exdates.iter().map(|exdate| occurrence_start(start, *exdate)).collect();
+
+        Some(CalendarEvent { summary: self.summary.clone(), start, duration, repeat, exceptions })
+    }
+}
+
+// Start of the occurrence of an event starting at `start` that an EXDATE or RECURRENCE-ID names,
+// a date on its own names the occurrence on that day
+fn occurrence_start(start: DateTime<Tz>, (value, all_day): (DateTime<Tz>, bool)) -> DateTime<Utc> {
+    let tz = start.timezone();
+    let named = match all_day {
+        true => resolve_local(tz, value.naive_local().date().and_time(start.naive_local().time())).0,
+        false => value,
+    };
+    named.with_timezone(&Utc)
+}
+
+// A DATE or DATE-TIME value, with whether it was a whole day. UTC times end in Z, others are in
+// the TZID given or in `default_tz` when there is none or it is not known
+fn parse_date_time(value: &str, tzid: Option<&str>, default_tz: Tz) -> Option<(DateTime<Tz>, bool)> {
+    let tz = tzid.and_then(|tzid| tzid.trim().parse().ok()).unwrap_or(default_tz);
+    if let Some(utc_value) = value.strip_suffix(['Z', 'z']) {
+        let naive = NaiveDateTime::parse_from_str(utc_value, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive).with_timezone(&tz), false));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Some((resolve_local(tz, naive).0, false));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+    Some((resolve_local(tz, date.and_hms_opt(0, 0, 0).unwrap()).0, true))
+}
+
+// A DURATION value such as PT1H30M or P1D
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {},
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let amount: i64 = number.parse().ok()?;
+                seconds += amount * match c {
+                    'W' => 7 * 24 * 60 * 60,
+                    'D' => 24 * 60 * 60,
+                    'H' => 60 * 60,
+                    'M' => 60,
+                    _ => 1,
+                };
+                number.clear();
+            },
+            _ => return None,
+        }
+    }
+    Some(Duration::seconds(if negative { -seconds } else { seconds }))
+}
+
+// An RRULE value for an event starting in `tz`, or None for rules other than daily and weekly ones
+fn parse_rule(rule: &str, tz: Tz) -> Option<Repeat> {
+    let mut frequency = None;
+    let mut interval = 1;
+    let mut weekdays = vec![];
+    let mut count = None;
+    let mut until = None;
+    for part in rule.split(';') {
+        let (name, value) = part.split_at(part.find('=')?);
+        let value = &value[1..];
+        match name.trim().to_uppercase().as_str() {
+            "FREQ" => frequency = Some(value.to_uppercase()),
+            "INTERVAL" => interval = value.parse::<i64>().ok()?.max(1),
+            "COUNT" => count = Some(value.parse().ok()?),
+            // A date on its own takes in the whole of that day
+            "UNTIL" => until = match parse_date_time(value, None, tz)? {
+                (until, true) => Some(start_of_day(tz, until.naive_local().date().succ_opt().unwrap()) - Duration::seconds(1)),
+                (until, false) => Some(until),
+            }.map(|until| until.with_timezone(&Utc)),
+            "BYDAY" => weekdays = WEEKDAYS.iter()
+                .filter(|(_, code)| value.split(',').any(|day| day.trim().eq_ignore_ascii_case(code)))
+                .map(|(weekday, _)| *weekday)
+                .collect(),
+            "WKST" => {},
+            _ => return None,
+        }
+    }
+
+    match frequency.as_deref() {
+        Some("DAILY") if weekdays.is_empty() => Some(Repeat { interval_days: interval, weekdays, count, until }),
+        Some("WEEKLY") => Some(Repeat { interval_days: interval * 7, weekdays, count, until }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America, Europe};
+    use crate::current_timezone_range;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+        BEGIN:VEVENT\r\nSUMMARY:Stand-up\r\nDTSTART;TZID=America/New_York:20261005T093000\r\n\
+        DURATION:PT15M\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=10\r\n\
+        BEGIN:VALARM\r\nDURATION:PT1H\r\nEND:VALARM\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nSUMMARY:Review\r\nDTSTART:20261021T140000Z\r\nDTEND:20261021T150000Z\r\nEND:VEVENT\r\n\
+        BEGIN:VEVENT\r\nSUMMARY:Lunch\r\nDTSTART:20261021T120000\r\nDTEND:20261021T130000\r\nTRANSP:TRANSPARENT\r\nEND:VEVENT\r\n\
+        END:VCALENDAR\r\n\
+        BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20261022\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+    // Starts in New York time of the first event's occurrences in October and November 2026
+    fn starts(calendar: &str) -> Vec<String> {
+        let events = events_from_ics(calendar, Europe::London).unwrap();
+        let from = Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+        events[0].occurrences(from, from + Duration::days(60)).iter()
+            .map(|(start, _)| start.with_timezone(&America::New_York).format("%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn events_are_read_from_every_calendar() {
+        let events = events_from_ics(CALENDAR, Europe::London).unwrap();
+        let summaries: Vec<&str> = events.iter().map(|event| event.summary.as_str()).collect();
+
+        assert_eq!(summaries, vec!["Stand-up", "Review", "Holiday"]);
+        assert_eq!(events[0].duration, Duration::minutes(15));
+        assert_eq!(events[0].repeat.as_ref().map(|repeat| repeat.weekdays.clone()), Some(vec![Weekday::Mon, Weekday::Wed]));
+        assert_eq!(events[2].start, Europe::London.with_ymd_and_hms(2026, 10, 22, 0, 0, 0).unwrap());
+        assert_eq!(events[2].duration, Duration::days(1));
+        assert!(events_from_ics("zones = []", Europe::London).is_err());
+    }
+
+    #[test]
+    fn repeats_keep_their_local_time() {
+        let starts = starts(CALENDAR);
+
+        // Ten in all, the last after the clocks go back on 1 November
+        assert_eq!(starts.len(), 10);
+        assert_eq!(starts[0], "10-05 09:30");
+        assert_eq!(starts[1], "10-07 09:30");
+        assert_eq!(starts[9], "11-04 09:30");
+    }
+
+    #[test]
+    fn repeats_in_a_clock_change_gap_move_forward() {
+        let calendar = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Night shift handover\r\n\
+            DTSTART;TZID=Europe/Berlin:20260315T023000\r\nDURATION:PT30M\r\nRRULE:FREQ=WEEKLY;COUNT=4\r\n\
+            END:VEVENT\r\nEND:VCALENDAR\r\n";
+        let events = events_from_ics(calendar, Europe::London).unwrap();
+        let from = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+
+        // 02:30 does not happen on 29 March, the first time after the clocks go forward stands in
+        let starts: Vec<String> = events[0].occurrences(from, from + Duration::days(60)).iter()
+            .map(|(start, _)| start.with_timezone(&Europe::Berlin).format("%m-%d %H:%M").to_string())
+            .collect();
+        assert_eq!(starts, vec!["03-15 02:30", "03-22 02:30", "03-29 03:00", "04-05 02:30"]);
+    }
+
+    #[test]
+    fn busy_spans_cover_the_base_day() {
+        let events = events_from_ics(CALENDAR, Europe::London).unwrap();
+        let zone_day = current_timezone_range(Europe::London, America::New_York, NaiveDate::from_ymd_opt(2026, 10, 21).unwrap());
+
+        // Stand-up 14:30 - 14:45 and the review 15:00 - 16:00 London time, the holiday from midnight
+        let spans = busy_day_spans(&events, &zone_day);
+        assert_eq!(spans, vec![(14.5 / 24.0, 14.75 / 24.0), (15.0 / 24.0, 16.0 / 24.0)]);
+        let zone_day = current_timezone_range(Europe::London, America::New_York, NaiveDate::from_ymd_opt(2026, 10, 22).unwrap());
+        assert_eq!(busy_day_spans(&events, &zone_day), vec![(0.0, 1.0)]);
+    }
+
+    #[test]
+    fn excluded_dates_are_left_out() {
+        let calendar = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:1:1\r\n\
+            DTSTART;TZID=America/New_York:20261005T093000\r\nRRULE:FREQ=WEEKLY;BYDAY=MO;COUNT=4\r\n\
+            EXDATE;TZID=America/New_York:20261012T093000\r\nEXDATE;VALUE=DATE:20261019\r\n\
+            END:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        // Left out occurrences still count towards the four
+        assert_eq!(starts(calendar), vec!["10-05 09:30", "10-26 09:30"]);
+    }
+
+    #[test]
+    fn an_override_replaces_the_occurrence_it_names() {
+        let calendar = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\nUID:sync\r\nSUMMARY:Sync\r\nDTSTART;TZID=Europe/London:20261019T100000\r\n\
+            DTEND;TZID=Europe/London:20261019T103000\r\nRRULE:FREQ=DAILY;COUNT=5\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:sync\r\nSUMMARY:Sync\r\nRECURRENCE-ID;TZID=Europe/London:20261021T100000\r\n\
+            DTSTART;TZID=Europe/London:20261021T150000\r\nDTEND;TZID=Europe/London:20261021T160000\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:sync\r\nRECURRENCE-ID;TZID=Europe/London:20261022T100000\r\n\
+            DTSTART;TZID=Europe/London:20261022T100000\r\nSTATUS:CANCELLED\r\nEND:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let events = events_from_ics(calendar, Europe::London).unwrap();
+        let spans = |day| busy_day_spans(&events, &current_timezone_range(Europe::London, Europe::London, NaiveDate::from_ymd_opt(2026, 10, day).unwrap()));
+
+        assert_eq!(spans(20), vec![(10.0 / 24.0, 10.5 / 24.0)]);
+        assert_eq!(spans(21), vec![(15.0 / 24.0, 16.0 / 24.0)]);
+        assert!(spans(22).is_empty());
+        assert_eq!(spans(23), vec![(10.0 / 24.0, 10.5 / 24.0)]);
+    }
+
+    #[test]
+    fn until_without_a_time_takes_in_that_day() {
+        let calendar = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Training\r\n\
+            DTSTART;TZID=America/New_York:20261019T180000\r\nRRULE:FREQ=DAILY;UNTIL=20261021\r\n\
+            END:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        assert_eq!(starts(calendar), vec!["10-19 18:00", "10-20 18:00", "10-21 18:00"]);
+    }
+
+    #[test]
+    fn repeats_without_an_end_reach_any_day() {
+        let calendar = "BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Planning\r\nDTSTART;TZID=Europe/London:19900101T090000\r\n\
+            DURATION:PT1H\r\nRRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nSUMMARY:Kick-off\r\nDTSTART;TZID=Europe/London:19900101T120000\r\n\
+            DURATION:PT1H\r\nRRULE:FREQ=DAILY;COUNT=3\r\nEND:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+        let events = events_from_ics(calendar, Europe::London).unwrap();
+        let spans = |day| busy_day_spans(&events, &current_timezone_range(Europe::London, Europe::London, NaiveDate::from_ymd_opt(2026, 10, day).unwrap()));
+
+        // Every other Monday since 1990, and nothing left of the three kick-offs
+        assert_eq!(spans(19), vec![(9.0 / 24.0, 10.0 / 24.0)]);
+        assert!(spans(26).is_empty());
+    }
+}
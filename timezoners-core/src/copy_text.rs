@@ -0,0 +1,89 @@
+use chrono::{DateTime, SecondsFormat};
+use chrono_tz::Tz;
+
+use crate::{format_utc_offset, utc_offset_minutes};
+
+/// Ways the selected time can be copied for pasting elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// One line, rows separated by ` / `.
+    PlainText,
+    /// A table with a line for each row.
+    Markdown,
+    /// A bulleted list with bold row names.
+    Slack,
+    /// A line for each row with the full date, time and UTC offset.
+    Iso8601,
+}
+
+impl CopyFormat {
+    pub const ALL: [CopyFormat; 4] = [CopyFormat::PlainText, CopyFormat::Markdown, CopyFormat::Slack, CopyFormat::Iso8601];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CopyFormat::PlainText => "plain text",
+            CopyFormat::Markdown => "Markdown table",
+            CopyFormat::Slack => "Slack message",
+            CopyFormat::Iso8601 => "ISO 8601",
+        }
+    }
+}
+
+/// Formats one instant as the local time of each row, given as `(name, time)` in row order.
+pub fn format_times(format: CopyFormat, rows: &[(String, DateTime<Tz>)]) -> String {
+    match format {
+        CopyFormat::PlainText => rows.iter()
+            .map(|(name, time)| format!("{} {}", name, time.format("%-I:%M %P %a")))
+            .collect::<Vec<_>>()
+            .join(" / "),
+        CopyFormat::Markdown => {
+            let mut lines = vec![
+                String::from("| Timezone | Time | Date | UTC offset |"),
+                String::from("|---|---|---|---|"),
+            ];
+            lines.extend(rows.iter().map(|(name, time)| format!("| {} | {} | {} | {} |",
+                name.replace('|', "\\|"),
+                time.format("%-I:%M %P"),
+                time.format("%a %Y-%m-%d"),
+                format_utc_offset(utc_offset_minutes(time)))));
+            lines.join("\n")
+        },
+        // Slack treats &, < and > as markup, so they are escaped in names
+        CopyFormat::Slack => rows.iter()
+            .map(|(name, time)| format!("• *{}*: {}",
+                name.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+                time.format("%-I:%M %P %a %-d %b (%Z)")))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        CopyFormat::Iso8601 => rows.iter()
+            .map(|(name, time)| format!("{}: {}", name, time.to_rfc3339_opts(SecondsFormat::Secs, false)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use chrono_tz::{Australia, Europe};
+
+    #[test]
+    fn each_format_gives_every_row() {
+        let instant = Utc.with_ymd_and_hms(2026, 10, 20, 22, 0, 0).unwrap();
+        let rows = vec![
+            (String::from("Sydney"), instant.with_timezone(&Australia::Sydney)),
+            (String::from("London | Ops"), instant.with_timezone(&Europe::London)),
+        ];
+
+        assert_eq!(format_times(CopyFormat::PlainText, &rows), "Sydney 9:00 am Wed / London | Ops 11:00 pm Tue");
+        assert_eq!(format_times(CopyFormat::Markdown, &rows), "| Timezone | Time | Date | UTC offset |\n\
+            |---|---|---|---|\n\
+            | Sydney | 9:00 am | Wed 2026-10-21 | UTC+11:00 |\n\
+            | London \\| Ops | 11:00 pm | Tue 2026-10-20 | UTC+1:00 |");
+        assert_eq!(format_times(CopyFormat::Slack, &rows), "• *Sydney*: 9:00 am Wed 21 Oct (AEDT)\n\
+            • *London | Ops*: 11:00 pm Tue 20 Oct (BST)");
+        assert_eq!(format_times(CopyFormat::Iso8601, &rows), "Sydney: 2026-10-21T09:00:00+11:00\n\
+            London | Ops: 2026-10-20T23:00:00+01:00");
+    }
+}
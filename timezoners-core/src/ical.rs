@@ -0,0 +1,449 @@
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+
+use crate::{WorkHours, offset_transitions, utc_offset_minutes};
+use crate::zone::{SavedZone, ZoneEntry};
+use crate::zone_set::ZoneSet;
+
+// Longest line in octets before it has to be folded, RFC 5545 section 3.1
+const MAX_LINE_OCTETS: usize = 75;
+
+// Calendar property holding one row of a zone set, in the order of the rows
+const ROW_PROPERTY: &str = "X-TIMEZONERS-ROW";
+
+const WEEKDAYS: [(Weekday, &str); 7] = [
+    (Weekday::Mon, "MO"), (Weekday::Tue, "TU"), (Weekday::Wed, "WE"), (Weekday::Thu, "TH"),
+    (Weekday::Fri, "FR"), (Weekday::Sat, "SA"), (Weekday::Sun, "SU"),
+];
+
+/// Escapes `text` for a TEXT property value.
+pub fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Reverses `escape_text`.
+pub fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(escaped) => unescaped.push(escaped),
+            None => {},
+        }
+    }
+    unescaped
+}
+
+/// Splits a content line longer than 75 octets into a first line and continuation lines starting
+/// with a space, all ending in CRLF. Multi-byte characters are never split.
+pub fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+/// Content lines as the text of an iCalendar file.
+pub fn ics_text(lines: &[String]) -> String {
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// Content lines of an iCalendar file with folded lines joined back up and blank lines left out.
+pub fn unfold_lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')), lines.last_mut()) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ if line.is_empty() => {},
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// One content line split into its parts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+    /// Upper case name such as `DTSTART`.
+    pub name: String,
+    /// Upper case parameter names with their values, quotes taken off.
+    pub params: Vec<(String, String)>,
+    /// Value as it appears in the file, still escaped.
+    pub value: String,
+}
+
+impl Property {
+    /// Value of the parameter called `name` (upper case).
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(param_name, _)| param_name == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Splits a content line into its parts, or `None` if it has no value.
+pub fn parse_property(line: &str) -> Option<Property> {
+    let mut in_quotes = false;
+    let mut parts = vec![];
+    let mut part_start = 0;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                parts.push(&line[part_start..i]);
+                part_start = i + 1;
+            },
+            ':' if !in_quotes => {
+                parts.push(&line[part_start..i]);
+                let name = parts[0].trim().to_uppercase();
+                let params = parts[1..].iter()
+                    .filter_map(|param| {
+                        let (param_name, param_value) = param.split_at(param.find('=')?);
+                        Some((param_name.trim().to_uppercase(), param_value[1..].trim_matches('"').to_string()))
+                    })
+                    .collect();
+                return Some(Property { name, params, value: line[i + 1..].to_string() });
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+/// A VTIMEZONE component for `tz` covering the given years, as content lines. Each change of
+/// offset is listed as its own observance rather than as a repeating rule so that years when a
+/// timezone changed its rules come out right.
+pub fn vtimezone(tz: Tz, years: RangeInclusive<i32>) -> Vec<String> {
+    let from = Utc.with_ymd_and_hms(*years.start(), 1, 1, 0, 0, 0).unwrap();
+    let to = Utc.with_ymd_and_hms(*years.end() + 1, 1, 1, 0, 0, 0).unwrap();
+    let mut lines = vec!["BEGIN:VTIMEZONE".to_string(), format!("TZID:{}", tz.name())];
+
+    // Covers times before the first change, or the whole range for timezones that never change
+    let start = from.with_timezone(&tz);
+    let start_minutes = utc_offset_minutes(&start);
+    lines.extend(observance(&start, start_minutes, start_minutes));
+    for transition in offset_transitions(tz, from, to) {
+        lines.extend(observance(&transition.at, transition.from_minutes, transition.to_minutes));
+    }
+
+    lines.push("END:VTIMEZONE".to_string());
+    lines
+}
+
+// STANDARD or DAYLIGHT component for the offset starting at `at`
+fn observance(at: &DateTime<Tz>, from_minutes: i32, to_minutes: i32) -> Vec<String> {
+    let kind = if at.offset().dst_offset() == Duration::zero() { "STANDARD" } else { "DAYLIGHT" };
+    // The onset is given in local time as the clocks showed it before the change
+    let onset = at.naive_utc() + Duration::minutes(from_minutes as i64);
+
+    vec![
+        format!("BEGIN:{}", kind),
+        format!("DTSTART:{}", onset.format("%Y%m%dT%H%M%S")),
+        format!("TZOFFSETFROM:{}", utc_offset_text(from_minutes)),
+        format!("TZOFFSETTO:{}", utc_offset_text(to_minutes)),
+        format!("TZNAME:{}", escape_text(at.offset().abbreviation())),
+        format!("END:{}", kind),
+    ]
+}
+
+// UTC offset as "+HHMM" or "-HHMM"
+fn utc_offset_text(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    format!("{}{:02}{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+}
+
+/// `zone_set` as an iCalendar file with a VTIMEZONE for each of its timezones covering the given
+/// years. Labels, badges, colours and working hours are kept in `X-TIMEZONERS-ROW` properties so
+/// that `zone_set_from_ics` can read the rows back in order, other calendar apps ignore them.
+/// Zones that cannot be read are left out.
+pub fn zone_set_to_ics(zone_set: &ZoneSet, years: RangeInclusive<i32>) -> String {
+    let entries: Vec<ZoneEntry> = zone_set.zones.iter().filter_map(|saved_zone| saved_zone.to_entry().ok()).collect();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//timezoners//Zone set//EN".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(&zone_set.name)),
+    ];
+
+    for entry in entries.iter() {
+        lines.push(row_property(entry));
+    }
+
+    // Several rows can share a timezone but it is only defined once
+    let mut defined: Vec<Tz> = vec![];
+    for entry in entries.iter() {
+        if !defined.contains(&entry.tz) {
+            defined.push(entry.tz);
+            lines.extend(vtimezone(entry.tz, years.clone()));
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    ics_text(&lines)
+}
+
+/// A meeting to write out as an iCalendar invite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invite {
+    pub summary: String,
+    /// Start in the base timezone, whose TZID the start and end are given in.
+    pub start: DateTime<Tz>,
+    pub duration: Duration,
+    /// Name and timezone of each row, listed with their local times in the description.
+    pub rows: Vec<(String, Tz)>,
+}
+
+/// `invite` as an iCalendar file holding one event, with a VTIMEZONE for the base timezone and
+/// each row's timezone covering the years of the meeting. `stamp` is when the invite was made.
+pub fn invite_to_ics(invite: &Invite, stamp: DateTime<Utc>) -> String {
+    let base_tz = invite.start.timezone();
+    let end = invite.start + invite.duration;
+    let years = invite.start.year()..=end.year();
+    let local_time = |instant: DateTime<Tz>| instant.naive_local().format("%Y%m%dT%H%M%S");
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//timezoners//Invite//EN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+    ];
+
+    let mut defined = vec![base_tz];
+    lines.extend(vtimezone(base_tz, years.clone()));
+    for (_, tz) in invite.rows.iter() {
+        if !defined.contains(tz) {
+            defined.push(*tz);
+            lines.extend(vtimezone(*tz, years.clone()));
+        }
+    }
+
+    let description: Vec<String> = invite.rows.iter()
+        .map(|(name, tz)| format!("{}: {}", name, local_span(invite.start.with_timezone(tz), end.with_timezone(tz))))
+        .collect();
+    lines.extend(vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}-{}@timezoners", invite.start.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ"), stamp.timestamp()),
+        format!("DTSTAMP:{}", stamp.format("%Y%m%dT%H%M%SZ")),
+        format!("DTSTART;TZID={}:{}", base_tz.name(), local_time(invite.start)),
+        format!("DTEND;TZID={}:{}", base_tz.name(), local_time(end)),
+        format!("SUMMARY:{}", escape_text(&invite.summary)),
+        format!("DESCRIPTION:{}", escape_text(&description.join("\n"))),
+        "END:VEVENT".to_string(),
+        "END:VCALENDAR".to_string(),
+    ]);
+    ics_text(&lines)
+}
+
+// Start and end as local times, the date only given once when they fall on the same day
+fn local_span(start: DateTime<Tz>, end: DateTime<Tz>) -> String {
+    if start.date_naive() == end.date_naive() {
+        format!("{} - {} ({})", start.format("%-I:%M %P"), end.format("%-I:%M %P %a %-d %b %Y"), start.format("%Z"))
+    } else {
+        format!("{} - {} ({})", start.format("%-I:%M %P %a %-d %b"), end.format("%-I:%M %P %a %-d %b %Y"), start.format("%Z"))
+    }
+}
+
+fn row_property(entry: &ZoneEntry) -> String {
+    let colour_text = |colour: (f64, f64, f64, f64)| format!("\"{},{},{},{}\"", colour.0, colour.1, colour.2, colour.3);
+    let weekdays: Vec<&str> = WEEKDAYS.iter()
+        .filter(|(weekday, _)| entry.work_hours.weekdays.contains(weekday))
+        .map(|(_, code)| *code)
+        .collect();
+
+    let mut property = format!("{};TZID={};X-WORK-HOURS=\"{}-{}\";X-WORKDAYS={}",
+        ROW_PROPERTY,
+        entry.tz.name(),
+        entry.work_hours.start.format("%H:%M"),
+        entry.work_hours.end.format("%H:%M"),
+        weekdays.join(","));
+    if entry.show_badge {
+        property.push_str(";X-BADGE=TRUE");
+    }
+    if let Some(colour) = entry.midday_colour {
+        property.push_str(&format!(";X-MIDDAY-COLOUR={}", colour_text(colour)));
+    }
+    if let Some(colour) = entry.workday_colour {
+        property.push_str(&format!(";X-WORKDAY-COLOUR={}", colour_text(colour)));
+    }
+    format!("{}:{}", property, escape_text(entry.label.as_deref().unwrap_or("")))
+}
+
+/// Reads a zone set from an iCalendar file. Files written by `zone_set_to_ics` give back their
+/// rows, any other calendar gives a row for each VTIMEZONE in it. The set is named after the
+/// calendar, or `default_name` if the calendar has no name. Timezone names are kept as they are
+/// so that unknown ones are reported when the set is loaded.
+pub fn zone_set_from_ics(text: &str, default_name: &str) -> Result<ZoneSet, String> {
+    let lines = unfold_lines(text);
+    if !matches!(lines.first(), Some(line) if line.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("not an iCalendar file".to_string());
+    }
+
+    let mut zone_set = ZoneSet::new(default_name);
+    let mut vtimezone_zones = vec![];
+    let mut in_vtimezone = false;
+    for line in lines.iter() {
+        let property = match parse_property(line) {
+            Some(property) => property,
+            None => continue,
+        };
+        let value = property.value.trim();
+        match property.name.as_str() {
+            "BEGIN" if value.eq_ignore_ascii_case("VTIMEZONE") => in_vtimezone = true,
+            "END" if value.eq_ignore_ascii_case("VTIMEZONE") => in_vtimezone = false,
+            "TZID" if in_vtimezone => vtimezone_zones.push(SavedZone::Name(value.to_string())),
+            "X-WR-CALNAME" if !value.is_empty() => zone_set.name = unescape_text(value),
+            ROW_PROPERTY => zone_set.zones.push(parse_row(&property)?),
+            _ => {},
+        }
+    }
+
+    if zone_set.zones.is_empty() {
+        zone_set.zones = vtimezone_zones;
+    }
+    if zone_set.zones.is_empty() {
+        return Err("no timezones found in the calendar".to_string());
+    }
+    Ok(zone_set)
+}
+
+fn parse_row(property: &Property) -> Result<SavedZone, String> {
+    let param = |name: &str| property.param(name);
+    let colour = |name: &str| -> Option<(f64, f64, f64, f64)> {
+        let parts: Vec<f64> = param(name)?.split(',').filter_map(|part| part.trim().parse().ok()).collect();
+        match parts.as_slice() {
+            [red, green, blue, alpha] => Some((*red, *green, *blue, *alpha)),
+            _ => None,
+        }
+    };
+
+    let zone = param("TZID").ok_or_else(|| format!("{} without a TZID", ROW_PROPERTY))?;
+    let mut work_hours = WorkHours::default();
+    if let Some((start, end)) = param("X-WORK-HOURS").and_then(|hours| {
+        let (start, end) = hours.split_at(hours.find('-')?);
+        Some((NaiveTime::parse_from_str(start, "%H:%M").ok()?, NaiveTime::parse_from_str(&end[1..], "%H:%M").ok()?))
+    }) {
+        work_hours.start = start;
+        work_hours.end = end;
+    }
+    if let Some(workdays) = param("X-WORKDAYS") {
+        work_hours.weekdays = WEEKDAYS.iter()
+            .filter(|(_, code)| workdays.split(',').any(|day| day.trim().eq_ignore_ascii_case(code)))
+            .map(|(weekday, _)| *weekday)
+            .collect();
+    }
+    let label = unescape_text(property.value.trim());
+
+    Ok(SavedZone::Entry {
+        zone: zone.to_string(),
+        label: if label.is_empty() { None } else { Some(label) },
+        show_badge: matches!(param("X-BADGE"), Some(badge) if badge.eq_ignore_ascii_case("TRUE")),
+        midday_colour: colour("X-MIDDAY-COLOUR"),
+        workday_colour: colour("X-WORKDAY-COLOUR"),
+        work_hours,
+        // Calendar files are paths on the machine that wrote them, so are not exported
+        calendar: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{Asia, Europe};
+
+    #[test]
+    fn vtimezone_lists_each_change() {
+        let lines = vtimezone(Europe::Berlin, 2021..=2021);
+        let text = lines.join("\n");
+
+        assert_eq!(lines.iter().filter(|line| line.starts_with("BEGIN:DAYLIGHT")).count(), 1);
+        assert_eq!(lines.iter().filter(|line| line.starts_with("BEGIN:STANDARD")).count(), 2);
+        // Clocks went forward at 2am local time on 28 March and back at 3am on 31 October
+        assert!(text.contains("BEGIN:DAYLIGHT\nDTSTART:20210328T020000\nTZOFFSETFROM:+0100\nTZOFFSETTO:+0200\nTZNAME:CEST"));
+        assert!(text.contains("DTSTART:20211031T030000\nTZOFFSETFROM:+0200\nTZOFFSETTO:+0100\nTZNAME:CET"));
+
+        let lines = vtimezone(Asia::Kolkata, 2021..=2022);
+        assert!(lines.contains(&"TZOFFSETTO:+0530".to_string()));
+        assert_eq!(lines.iter().filter(|line| line.starts_with("BEGIN:")).count(), 2);
+    }
+
+    #[test]
+    fn long_lines_fold_and_unfold() {
+        let line = format!("X-WR-CALNAME:{}", "Zürich ".repeat(20));
+        let folded = fold_line(&line);
+
+        assert!(folded.split("\r\n").all(|part| part.len() <= MAX_LINE_OCTETS));
+        assert_eq!(unfold_lines(&folded), vec![line]);
+        assert_eq!(unescape_text(&escape_text("a, b; c\\d\ne")), "a, b; c\\d\ne");
+    }
+
+    #[test]
+    fn zone_set_round_trips_through_ics() {
+        let mut alice = ZoneEntry::new(Europe::Berlin);
+        alice.label = Some("Alice; Berlin, Germany".to_string());
+        alice.show_badge = true;
+        alice.midday_colour = Some((0.5, 0.25, 1.0, 0.75));
+        alice.work_hours.start = NaiveTime::from_hms_opt(22, 0, 0).unwrap();
+        alice.work_hours.end = NaiveTime::from_hms_opt(6, 30, 0).unwrap();
+        alice.work_hours.weekdays = vec![Weekday::Sat, Weekday::Sun];
+        let zone_set = ZoneSet {
+            name: "EU customers".to_string(),
+            zones: vec![SavedZone::from(&ZoneEntry::new(Asia::Kolkata)), SavedZone::from(&alice), SavedZone::from(&ZoneEntry::new(Europe::Berlin))],
+        };
+
+        let text = zone_set_to_ics(&zone_set, 2021..=2021);
+        assert_eq!(text.matches("BEGIN:VTIMEZONE").count(), 2);
+        assert_eq!(zone_set_from_ics(&text, "imported"), Ok(zone_set));
+    }
+
+    #[test]
+    fn invite_is_in_the_base_timezone() {
+        let invite = Invite {
+            summary: "Planning, Q4".to_string(),
+            start: Europe::London.with_ymd_and_hms(2026, 10, 20, 23, 30, 0).unwrap(),
+            duration: Duration::minutes(60),
+            rows: vec![("London".to_string(), Europe::London), ("Alice".to_string(), Asia::Kolkata), ("Office".to_string(), Europe::London)],
+        };
+        let text = invite_to_ics(&invite, Utc.with_ymd_and_hms(2026, 10, 18, 9, 0, 0).unwrap());
+        let lines = unfold_lines(&text);
+
+        assert!(text.split("\r\n").all(|part| part.len() <= MAX_LINE_OCTETS));
+        assert_eq!(text.matches("BEGIN:VTIMEZONE").count(), 2);
+        assert!(lines.contains(&"DTSTART;TZID=Europe/London:20261020T233000".to_string()));
+        assert!(lines.contains(&"DTEND;TZID=Europe/London:20261021T003000".to_string()));
+        assert!(lines.contains(&"DTSTAMP:20261018T090000Z".to_string()));
+        assert!(lines.contains(&"SUMMARY:Planning\\, Q4".to_string()));
+        let description = lines.iter().find_map(|line| line.strip_prefix("DESCRIPTION:")).unwrap();
+        assert_eq!(unescape_text(description), "London: 11:30 pm Tue 20 Oct - 12:30 am Wed 21 Oct 2026 (BST)\n\
+            Alice: 4:00 am - 5:00 am Wed 21 Oct 2026 (IST)\n\
+            Office: 11:30 pm Tue 20 Oct - 12:30 am Wed 21 Oct 2026 (BST)");
+    }
+
+    #[test]
+    fn plain_calendar_gives_a_row_per_vtimezone() {
+        let text = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VTIMEZONE\r\nTZID:America/New_York\r\nEND:VTIMEZONE\r\n\
+                    BEGIN:VTIMEZONE\r\nTZID:W. Europe Standard Time\r\nEND:VTIMEZONE\r\nEND:VCALENDAR\r\n";
+
+        let zone_set = zone_set_from_ics(text, "team.ics").unwrap();
+        assert_eq!(zone_set.name, "team.ics");
+        assert_eq!(zone_set.zones, vec![SavedZone::Name("America/New_York".to_string()), SavedZone::Name("W. Europe Standard Time".to_string())]);
+        assert!(zone_set_from_ics("zones = []", "x").is_err());
+    }
+}
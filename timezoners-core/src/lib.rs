@@ -0,0 +1,379 @@
+//! Time calculations used to line up several timezones against the day of a base timezone.
+//!
+//! The base timezone defines a day running from its first instant on a given date to the first
+//! instant of the next date. That is usually 24 hours but can be 23 or 25 hours (or some other
+//! length) when the base timezone changes its clocks that day. Every other timezone is shown over
+//! the same span of absolute time, so its local day usually starts and ends at some other time of
+//! day (and possibly on another date). Positions along that day are expressed either as a fraction
+//! of the day (`0.0` - `1.0`) or as a slot index where each slot is one of the [`SLOT_SIZES`]
+//! minutes long.
+
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike};
+use chrono_tz::Tz;
+
+mod calendar;
+mod copy_text;
+mod ical;
+mod meeting;
+mod time_entry;
+mod transitions;
+mod workhours;
+mod zone;
+mod zone_info;
+mod zone_set;
+
+pub use crate::calendar::{CalendarEvent, Repeat, busy_day_spans, events_from_ics};
+pub use crate::copy_text::{CopyFormat, format_times};
+pub use crate::ical::{Invite, Property, escape_text, fold_line, ics_text, invite_to_ics, parse_property, unescape_text, unfold_lines, vtimezone, zone_set_from_ics, zone_set_to_ics};
+pub use crate::meeting::{DayOverlap, MeetingWindow, day_overlap, find_meeting_windows};
+pub use crate::time_entry::{EntryDate, EntryZone, TimeEntry, ZoneAbbreviation, abbreviation_meanings, find_time_entry, parse_time_entry};
+pub use crate::transitions::{OffsetTransition, OffsetWarning, offset_transitions, upcoming_offset_warnings};
+pub use crate::workhours::WorkHours;
+pub use crate::zone::{Colour, SavedZone, ZoneEntry, ZoneSort, compare_entries, label_initials, load_zones};
+pub use crate::zone_info::{ZoneInfo, format_utc_offset};
+pub use crate::zone_set::{DEFAULT_SET_NAME, ZoneSet, unique_set_name};
+
+/// Supported lengths of a single slot on the day bar in minutes, each divides an hour evenly.
+pub const SLOT_SIZES: [u32; 6] = [1, 5, 10, 15, 30, 60];
+
+/// Slot length used when none has been configured.
+pub const DEFAULT_SLOT_MINUTES: u32 = 15;
+
+const MINUTES_PER_DAY: f64 = 24.0 * 60.0;
+
+/// The span of the base day as seen from another timezone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneDay {
+    /// Date of the base day in the base timezone.
+    pub for_date: NaiveDate,
+    /// Local time in this timezone at the start of the base day.
+    pub start: DateTime<Tz>,
+    /// Local time in this timezone at the last second of the base day.
+    pub end: DateTime<Tz>,
+    /// Length of the base day in minutes, 1440 unless the base timezone changes its clocks.
+    pub length_minutes: i64,
+    /// False if `start` falls on the day before the base date.
+    pub start_same_date: bool,
+    /// False if `end` falls on the day after the base date.
+    pub end_same_date: bool,
+    /// Clock changes in this timezone during the base day.
+    pub clock_changes: Vec<ClockChange>,
+}
+
+/// A change of UTC offset in one timezone, such as the start or end of daylight saving.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClockChange {
+    /// First instant with the new offset.
+    pub at: DateTime<Tz>,
+    /// Minutes the clocks moved, positive when local time is skipped and negative when repeated.
+    pub minutes: i32,
+    /// Fraction of the base day at which the change happens.
+    pub offset: f64,
+}
+
+impl ZoneDay {
+    /// Number of slots of `slot_minutes` covering the base day.
+    pub fn slot_count(&self, slot_minutes: u32) -> u32 {
+        (self.length_minutes / slot_minutes as i64) as u32
+    }
+
+    /// Local time in this timezone at slot `index`.
+    pub fn time_at_index(&self, index: f64, slot_minutes: u32) -> DateTime<Tz> {
+        self.start + Duration::minutes(index as i64 * slot_minutes as i64)
+    }
+
+    /// Fraction of the base day at which `instant` falls, outside `0.0` - `1.0` if not in the day.
+    pub fn offset_for_instant(&self, instant: DateTime<Tz>) -> f64 {
+        // A base date skipped entirely (Pacific/Apia 2011-12-30) has no length at all
+        (instant - self.start).num_seconds() as f64 / (self.length_minutes.max(1) * 60) as f64
+    }
+}
+
+/// First instant of `for_date` in `tz`.
+///
+/// Midnight is used where it exists, the earlier one if the clocks go back over it. If the
+/// clocks skip midnight the day starts at the first local time that does exist.
+pub fn start_of_day(tz: Tz, for_date: NaiveDate) -> DateTime<Tz> {
+    resolve_local(tz, for_date.and_hms_opt(0, 0, 0).unwrap()).0
+}
+
+// Earliest and latest instant showing `local` in `tz`, the same unless the clocks went back over it.
+// A local time skipped by the clocks going forward resolves to the first local time after the gap
+pub(crate) fn resolve_local(tz: Tz, mut local: NaiveDateTime) -> (DateTime<Tz>, DateTime<Tz>) {
+    loop {
+        match tz.from_local_datetime(&local) {
+            LocalResult::Single(instant) => return (instant, instant),
+            LocalResult::Ambiguous(earliest, latest) => return (earliest, latest),
+            LocalResult::None => local += Duration::minutes(1),
+        }
+    }
+}
+
+/// Start and last second of `for_date` in the base timezone.
+pub fn base_timezone_range(base_tz: Tz, for_date: NaiveDate) -> (DateTime<Tz>, DateTime<Tz>) {
+    let base_start_time_tz = start_of_day(base_tz, for_date);
+    let base_end_time_tz = start_of_day(base_tz, for_date.succ_opt().unwrap()) - Duration::seconds(1);
+
+    (base_start_time_tz, base_end_time_tz)
+}
+
+/// Start and end of the base day for `for_date` expressed in `this_tz`.
+pub fn current_timezone_range(base_tz: Tz, this_tz: Tz, for_date: NaiveDate) -> ZoneDay {
+    let (base_start_time_tz, base_end_time_tz) = base_timezone_range(base_tz, for_date);
+
+    let start = base_start_time_tz.with_timezone(&this_tz);
+    let end = base_end_time_tz.with_timezone(&this_tz);
+    let length_minutes = (end - start + Duration::seconds(1)).num_minutes();
+
+    let mut zone_day = ZoneDay {
+        for_date,
+        start_same_date: start.naive_local().date() >= for_date,
+        end_same_date: end.naive_local().date() <= for_date,
+        start,
+        end,
+        length_minutes,
+        clock_changes: vec![],
+    };
+    zone_day.clock_changes = find_clock_changes(&zone_day);
+    zone_day
+}
+
+/// Aligns each of `zones` against the base day for `for_date`, in the same order.
+pub fn align_zones(base_tz: Tz, zones: &[Tz], for_date: NaiveDate) -> Vec<ZoneDay> {
+    zones.iter().map(|this_tz| current_timezone_range(base_tz, *this_tz, for_date)).collect()
+}
+
+/// Formats a number of minutes as a signed amount of hours and minutes such as `+1h` or `-5h30m`.
+pub fn format_minutes(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let (hours, mins) = (minutes.abs() / 60, minutes.abs() % 60);
+    match (hours, mins) {
+        (0, mins) => format!("{}{}m", sign, mins),
+        (hours, 0) => format!("{}{}h", sign, hours),
+        (hours, mins) => format!("{}{}h{}m", sign, hours, mins),
+    }
+}
+
+/// UTC offset of `instant` in minutes.
+pub fn utc_offset_minutes(instant: &DateTime<Tz>) -> i32 {
+    instant.offset().fix().local_minus_utc() / 60
+}
+
+// Scans the day a quarter hour at a time and then narrows each change down to the minute
+fn find_clock_changes(zone_day: &ZoneDay) -> Vec<ClockChange> {
+    let step = Duration::minutes(15);
+    let mut changes = vec![];
+
+    let mut prev = zone_day.start;
+    while prev < zone_day.end {
+        let next = std::cmp::min(prev + step, zone_day.end);
+        if utc_offset_minutes(&next) != utc_offset_minutes(&prev) {
+            let (mut before, mut after) = (prev, next);
+            while after - before > Duration::minutes(1) {
+                let mid = before + (after - before) / 2;
+                if utc_offset_minutes(&mid) == utc_offset_minutes(&before) {
+                    before = mid;
+                } else {
+                    after = mid;
+                }
+            }
+            changes.push(ClockChange {
+                minutes: utc_offset_minutes(&after) - utc_offset_minutes(&before),
+                offset: zone_day.offset_for_instant(after),
+                at: after,
+            });
+        }
+        prev = next;
+    }
+
+    changes
+}
+
+/// Local times in the zone of `zone_day` every `step_minutes` across the base day.
+pub fn slot_times(zone_day: &ZoneDay, step_minutes: u32) -> Vec<DateTime<Tz>> {
+    (0..zone_day.slot_count(step_minutes))
+        .map(|slot| zone_day.time_at_index(slot as f64, step_minutes))
+        .collect()
+}
+
+/// Formats the local time at slot `index` of `zone_day` for slots of `slot_minutes`.
+///
+/// Times before the base date are prefixed with `<=` and times after it are suffixed with `=>`.
+pub fn time_string_from_index(index: f64, zone_day: &ZoneDay, slot_minutes: u32) -> String {
+    let calc_time = zone_day.time_at_index(index, slot_minutes);
+
+    let calc_date = calc_time.naive_local().date();
+    if calc_date < zone_day.for_date {
+        format!("{}", calc_time.format("<= %I:%M %P"))
+    } else if calc_date > zone_day.for_date {
+        format!("{}", calc_time.format("%I:%M %P =>"))
+    } else {
+        format!("{}", calc_time.format("%I:%M %P"))
+    }
+}
+
+/// Slot index in `this_tz` for a local time of day, rounded to the nearest slot of `slot_minutes`.
+pub fn index_from_time(base_tz: Tz, this_tz: Tz, for_date: NaiveDate, ref_time: NaiveTime, slot_minutes: u32) -> f64 {
+    let zone_day = current_timezone_range(base_tz, this_tz, for_date);
+
+    let rounded = round_to_slot(ref_time, slot_minutes);
+    let minutes = (next_occurrence(&zone_day, rounded) - zone_day.start).num_minutes();
+    (minutes as f64 / slot_minutes as f64).round()
+}
+
+/// Rounds a time of day to the nearest multiple of `slot_minutes`, wrapping past midnight.
+pub fn round_to_slot(time: NaiveTime, slot_minutes: u32) -> NaiveTime {
+    let minutes = time.hour() * 60 + time.minute();
+    let mut slot = minutes / slot_minutes;
+    if (minutes % slot_minutes) as f64 / slot_minutes as f64 > 0.5 {
+        slot += 1;
+    }
+    let rounded = (slot * slot_minutes) % (24 * 60);
+
+    NaiveTime::from_hms_opt(rounded / 60, rounded % 60, 0).unwrap()
+}
+
+// First instant at or after the start of the day showing local time `ref_time`
+fn next_occurrence(zone_day: &ZoneDay, ref_time: NaiveTime) -> DateTime<Tz> {
+    let this_tz = zone_day.start.timezone();
+    let start_date = zone_day.start.naive_local().date();
+
+    for date in [start_date, start_date + Duration::days(1), start_date + Duration::days(2)].iter() {
+        let (earliest, latest) = resolve_local(this_tz, date.and_time(ref_time));
+        if earliest >= zone_day.start {
+            return earliest;
+        }
+        if latest >= zone_day.start {
+            return latest;
+        }
+    }
+
+    zone_day.start
+}
+
+/// Fraction of the day bar at which midday falls in the zone of `zone_day`.
+pub fn offset_for_midday(zone_day: &ZoneDay) -> f64 {
+    offset_for_time(zone_day, NaiveTime::from_hms_opt(12, 0, 0).unwrap())
+}
+
+/// Fraction of the local day that has already passed at `curr_start_time_tz`.
+pub fn day_percent_complete(curr_start_time_tz: DateTime<Tz>) -> f64 {
+    let full_day = NaiveTime::from_hms_opt(23, 59, 59).unwrap();
+    let nv_curr = NaiveTime::from_hms_opt(curr_start_time_tz.hour(), curr_start_time_tz.minute(), curr_start_time_tz.second()).unwrap();
+
+    1.0 - (full_day - nv_curr).num_minutes() as f64 / MINUTES_PER_DAY
+}
+
+/// Fraction of the day bar at which local time `ref_time` next falls in the zone of `zone_day`.
+///
+/// This is greater than `1.0` when a short base day ends before that time comes around.
+pub fn offset_for_time(zone_day: &ZoneDay, ref_time: NaiveTime) -> f64 {
+    zone_day.offset_for_instant(next_occurrence(zone_day, ref_time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{America, Australia, Europe, US};
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2021, 1, 14).unwrap()
+    }
+
+    #[test]
+    fn pacific_against_canberra() {
+        let zone_day = current_timezone_range(Australia::Canberra, US::Pacific, date());
+
+        assert_eq!(zone_day.start.time(), NaiveTime::from_hms_opt(5, 0, 0).unwrap());
+        assert!(!zone_day.start_same_date);
+        assert!(zone_day.end_same_date);
+        assert_eq!(time_string_from_index(0.0, &zone_day, 15), "<= 05:00 am");
+        assert_eq!(time_string_from_index(80.0, &zone_day, 15), "01:00 am");
+        assert_eq!(time_string_from_index(20.0, &zone_day, 60), "01:00 am");
+    }
+
+    #[test]
+    fn index_rounds_to_nearest_slot() {
+        let base = Europe::London;
+        let at = |h, m| index_from_time(base, base, date(), NaiveTime::from_hms_opt(h, m, 0).unwrap(), DEFAULT_SLOT_MINUTES);
+
+        assert_eq!(at(11, 0), 44.0);
+        assert_eq!(at(11, 6), 44.0);
+        assert_eq!(at(11, 9), 45.0);
+        assert_eq!(at(23, 59), 0.0);
+    }
+
+    #[test]
+    fn finer_slots_reach_quarter_hour_zones() {
+        let base = Europe::London;
+        let kathmandu = chrono_tz::Asia::Kathmandu;
+
+        // 09:50 in Kathmandu is 04:05 in London
+        let index = index_from_time(base, kathmandu, date(), NaiveTime::from_hms_opt(9, 50, 0).unwrap(), 5);
+        assert_eq!(index, 49.0);
+        assert_eq!(current_timezone_range(base, kathmandu, date()).slot_count(5), 288);
+        assert_eq!(round_to_slot(NaiveTime::from_hms_opt(9, 50, 0).unwrap(), 15), NaiveTime::from_hms_opt(9, 45, 0).unwrap());
+        assert_eq!(round_to_slot(NaiveTime::from_hms_opt(23, 40, 0).unwrap(), 60), NaiveTime::from_hms_opt(0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn midday_offset_wraps_past_midnight() {
+        let zone_day = current_timezone_range(Australia::Canberra, US::Pacific, date());
+
+        assert_eq!(offset_for_midday(&zone_day), 7.0 / 24.0);
+        assert_eq!(align_zones(Australia::Canberra, &[US::Pacific], date()), vec![zone_day]);
+    }
+
+    #[test]
+    fn hourly_slots_cover_the_base_day() {
+        let zone_day = current_timezone_range(Australia::Canberra, US::Pacific, date());
+        let slots = slot_times(&zone_day, 60);
+
+        assert_eq!(slots.len(), 24);
+        assert_eq!(slots[0], zone_day.start);
+        assert_eq!(slots[23].time(), NaiveTime::from_hms_opt(4, 0, 0).unwrap());
+        assert_eq!(slots[23].naive_local().date(), date());
+    }
+
+    #[test]
+    fn skipped_midnight_starts_day_at_first_valid_time() {
+        // Sao Paulo moved from 00:00 straight to 01:00 on 2018-11-04
+        let for_date = NaiveDate::from_ymd_opt(2018, 11, 4).unwrap();
+        let zone_day = current_timezone_range(America::Sao_Paulo, America::Sao_Paulo, for_date);
+
+        assert_eq!(zone_day.start.time(), NaiveTime::from_hms_opt(1, 0, 0).unwrap());
+        assert_eq!(zone_day.length_minutes, 23 * 60);
+        assert_eq!(zone_day.slot_count(15), 92);
+        assert_eq!(time_string_from_index(0.0, &zone_day, 15), "01:00 am");
+        assert_eq!(time_string_from_index(91.0, &zone_day, 15), "11:45 pm");
+    }
+
+    #[test]
+    fn repeated_hour_makes_a_long_day() {
+        let for_date = NaiveDate::from_ymd_opt(2021, 10, 31).unwrap();
+        let london = current_timezone_range(Europe::London, Europe::London, for_date);
+
+        assert_eq!(london.length_minutes, 25 * 60);
+        assert_eq!(london.slot_count(15), 100);
+        assert_eq!(london.clock_changes.len(), 1);
+        assert_eq!(london.clock_changes[0].minutes, -60);
+        assert_eq!(london.clock_changes[0].at.time(), NaiveTime::from_hms_opt(1, 0, 0).unwrap());
+        assert_eq!(london.clock_changes[0].offset, 2.0 / 25.0);
+        assert_eq!(format_minutes(london.clock_changes[0].minutes), "-1h");
+        assert_eq!(format_minutes(345), "+5h45m");
+
+        // 01:30 happens twice, the first one is used, and 03:00 comes after the extra hour
+        let at = |h, m| index_from_time(Europe::London, Europe::London, for_date, NaiveTime::from_hms_opt(h, m, 0).unwrap(), 15);
+        assert_eq!(at(1, 30), 6.0);
+        assert_eq!(at(3, 0), 16.0);
+        assert_eq!(time_string_from_index(6.0, &london, 15), "01:30 am");
+        assert_eq!(time_string_from_index(10.0, &london, 15), "01:30 am");
+
+        // Berlin sees the change too but the base day is an ordinary one there
+        let berlin = current_timezone_range(Europe::London, Europe::Berlin, NaiveDate::from_ymd_opt(2021, 10, 30).unwrap());
+        assert_eq!(berlin.length_minutes, 24 * 60);
+        assert!(berlin.clock_changes.is_empty());
+        let berlin = current_timezone_range(Europe::London, Europe::Berlin, for_date);
+        assert_eq!(berlin.clock_changes[0].at.time(), NaiveTime::from_hms_opt(2, 0, 0).unwrap());
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::{DateTime, Duration, NaiveDate};
+use chrono_tz::Tz;
+
+use crate::{WorkHours, start_of_day};
+
+/// A span of time when every zone is inside its working hours.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeetingWindow {
+    /// Base date the window falls on.
+    pub for_date: NaiveDate,
+    /// Start of the window in the base timezone.
+    pub start: DateTime<Tz>,
+    /// End of the window in the base timezone.
+    pub end: DateTime<Tz>,
+    /// Smallest share of any zone's working day covered by the window, `0.0` - `1.0`.
+    pub score: f64,
+}
+
+impl MeetingWindow {
+    /// Length of the window in minutes.
+    pub fn minutes(&self) -> i64 {
+        (self.end - self.start).num_minutes()
+    }
+}
+
+/// Finds every window of at least `duration` on `for_date` and the `extra_days` after it during
+/// which all `zones` are inside their working hours.
+///
+/// Days are those of `base_tz` and a window never runs past the end of its base day. Times are
+/// checked every `step_minutes`. The best windows come first, ranked by how much of each zone's
+/// working day they take up so that a window filling a large part of everyone's day beats one
+/// that only just fits the meeting in.
+pub fn find_meeting_windows(base_tz: Tz, zones: &[(Tz, WorkHours)], for_date: NaiveDate, extra_days: u32, duration: Duration, step_minutes: u32) -> Vec<MeetingWindow> {
+    let mut windows = vec![];
+    if zones.is_empty() {
+        return windows;
+    }
+
+    let step = Duration::minutes(step_minutes as i64);
+    let mut day = for_date;
+    for _ in 0..=extra_days {
+        let day_end = start_of_day(base_tz, day.succ_opt().unwrap());
+        let mut window_start: Option<DateTime<Tz>> = None;
+        let mut close_window = |start: DateTime<Tz>, end: DateTime<Tz>| {
+            if end - start >= duration {
+                windows.push(MeetingWindow {
+                    for_date: day,
+                    start,
+                    end,
+                    score: score_window(zones, end - start),
+                });
+            }
+        };
+
+        let mut instant = start_of_day(base_tz, day);
+        while instant < day_end {
+            let all_working = zones.iter()
+                .all(|(this_tz, work_hours)| work_hours.contains(&instant.with_timezone(this_tz)));
+
+            match (all_working, window_start) {
+                (true, None) => window_start = Some(instant),
+                (false, Some(start)) => {
+                    close_window(start, instant);
+                    window_start = None;
+                },
+                _ => {},
+            }
+            instant += step;
+        }
+        // The last step can pass the end of a day that is not a whole number of steps long
+        if let Some(start) = window_start {
+            close_window(start, day_end);
+        }
+        day = day.succ_opt().unwrap();
+    }
+
+    windows.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap().then(a.start.cmp(&b.start)));
+    windows
+}
+
+/// Where the working hours of a set of zones overlap during one base day.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DayOverlap {
+    /// Parts of the day when every zone is working, as `(from, to)` fractions of the day.
+    pub shared: Vec<(f64, f64)>,
+    /// For each zone, the parts of the day when it is the only zone not working.
+    pub blocking: Vec<Vec<(f64, f64)>>,
+}
+
+/// Works out when all `zones` are working during `for_date` in `base_tz`, checked every
+/// `step_minutes`. Nothing is blocking with fewer than two zones.
+pub fn day_overlap(base_tz: Tz, zones: &[(Tz, WorkHours)], for_date: NaiveDate, step_minutes: u32) -> DayOverlap {
+    let mut overlap = DayOverlap {
+        shared: vec![],
+        blocking: vec![vec![]; zones.len()],
+    };
+    if zones.is_empty() {
+        return overlap;
+    }
+
+    let day_start = start_of_day(base_tz, for_date);
+    let day_minutes = (start_of_day(base_tz, for_date.succ_opt().unwrap()) - day_start).num_minutes().max(1);
+    let steps = day_minutes / step_minutes as i64;
+
+    for step in 0..steps {
+        let instant = day_start + Duration::minutes(step * step_minutes as i64);
+        let working: Vec<bool> = zones.iter()
+            .map(|(this_tz, work_hours)| work_hours.contains(&instant.with_timezone(this_tz)))
+            .collect();
+        let from = (step * step_minutes as i64) as f64 / day_minutes as f64;
+        let to = (((step + 1) * step_minutes as i64).min(day_minutes)) as f64 / day_minutes as f64;
+
+        let not_working: Vec<usize> = (0..zones.len()).filter(|zone| !working[*zone]).collect();
+        match not_working.as_slice() {
+            [] => add_span(&mut overlap.shared, from, to),
+            [only] if zones.len() > 1 => add_span(&mut overlap.blocking[*only], from, to),
+            _ => {},
+        }
+    }
+
+    overlap
+}
+
+// Appends a span, joining it to the previous one when they touch
+fn add_span(spans: &mut Vec<(f64, f64)>, from: f64, to: f64) {
+    match spans.last_mut() {
+        Some(last) if last.1 == from => last.1 = to,
+        _ => spans.push((from, to)),
+    }
+}
+
+fn score_window(zones: &[(Tz, WorkHours)], length: Duration) -> f64 {
+    zones.iter()
+        .map(|(_, work_hours)| (length.num_minutes() as f64 / work_hours.minutes() as f64).min(1.0))
+        .fold(1.0, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, Timelike, Weekday};
+    use chrono_tz::{Asia, Australia, Europe};
+
+    #[test]
+    fn only_blocking_zone_is_marked() {
+        let late = WorkHours {
+            start: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+            ..WorkHours::default()
+        };
+        let zones = [(Europe::London, WorkHours::default()), (Europe::London, WorkHours::default()), (Europe::London, late)];
+
+        let overlap = day_overlap(Europe::London, &zones, NaiveDate::from_ymd_opt(2021, 1, 14).unwrap(), 15);
+        assert_eq!(overlap.shared, vec![(12.0 / 24.0, 17.0 / 24.0)]);
+        assert_eq!(overlap.blocking[0], vec![]);
+        assert_eq!(overlap.blocking[2], vec![(8.0 / 24.0, 12.0 / 24.0)]);
+    }
+
+    #[test]
+    fn finds_overlap_between_berlin_and_kolkata() {
+        let germany = WorkHours {
+            start: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(15, 30, 0).unwrap(),
+            ..WorkHours::default()
+        };
+        let india = WorkHours {
+            start: NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(19, 0, 0).unwrap(),
+            ..WorkHours::default()
+        };
+        let zones = [(Europe::Berlin, germany), (Asia::Kolkata, india)];
+
+        // Kolkata is 4:30 ahead of Berlin in January, so 07:00 - 14:30 Berlin overlaps
+        let windows = find_meeting_windows(Europe::Berlin, &zones, NaiveDate::from_ymd_opt(2021, 1, 14).unwrap(), 0, Duration::minutes(60), 15);
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].start.time().hour(), 7);
+        assert_eq!(windows[0].end.time(), NaiveTime::from_hms_opt(14, 30, 0).unwrap());
+        assert_eq!(windows[0].minutes(), 7 * 60 + 30);
+
+        // Friday, then a weekend with nothing, then Monday with the same window
+        let windows = find_meeting_windows(Europe::Berlin, &zones, NaiveDate::from_ymd_opt(2021, 1, 15).unwrap(), 3, Duration::minutes(60), 15);
+        let dates: Vec<NaiveDate> = windows.iter().map(|window| window.for_date).collect();
+        assert_eq!(dates, vec![NaiveDate::from_ymd_opt(2021, 1, 15).unwrap(), NaiveDate::from_ymd_opt(2021, 1, 18).unwrap()]);
+
+        let too_long = find_meeting_windows(Europe::Berlin, &zones, NaiveDate::from_ymd_opt(2021, 1, 14).unwrap(), 0, Duration::hours(8), 15);
+        assert!(too_long.is_empty());
+    }
+
+    #[test]
+    fn window_open_at_the_end_of_a_short_day_is_kept() {
+        let evening = WorkHours {
+            start: NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            weekdays: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun],
+        };
+        let zones = [(Australia::Lord_Howe, evening)];
+
+        // Lord Howe moves from 02:00 to 02:30, so hourly steps land on the half hour and the 23h30
+        // day ends between two of them
+        let windows = find_meeting_windows(Australia::Lord_Howe, &zones, NaiveDate::from_ymd_opt(2026, 10, 4).unwrap(), 0, Duration::minutes(60), 60);
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].start.time(), NaiveTime::from_hms_opt(20, 30, 0).unwrap());
+        assert_eq!(windows[0].end, start_of_day(Australia::Lord_Howe, NaiveDate::from_ymd_opt(2026, 10, 5).unwrap()));
+        assert_eq!(windows[0].minutes(), 3 * 60 + 30);
+    }
+}
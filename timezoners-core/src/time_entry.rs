@@ -0,0 +1,517 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::{TZ_VARIANTS, Tz};
+
+use crate::resolve_local;
+
+/// A common timezone abbreviation and one of the things it stands for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ZoneAbbreviation {
+    pub abbreviation: &'static str,
+    pub name: &'static str,
+    pub utc_offset_minutes: i32,
+}
+
+const fn abbreviation(abbreviation: &'static str, name: &'static str, utc_offset_minutes: i32) -> ZoneAbbreviation {
+    ZoneAbbreviation { abbreviation, name, utc_offset_minutes }
+}
+
+// Abbreviations are not unique, the ones meaning more than one thing are listed once per meaning
+const ZONE_ABBREVIATIONS: [ZoneAbbreviation; 38] = [
+    abbreviation("UTC", "Coordinated Universal Time", 0),
+    abbreviation("GMT", "Greenwich Mean Time", 0),
+    abbreviation("Z", "Coordinated Universal Time", 0),
+    abbreviation("HST", "Hawaii Standard Time", -600),
+    abbreviation("AKST", "Alaska Standard Time", -540),
+    abbreviation("AKDT", "Alaska Daylight Time", -480),
+    abbreviation("PST", "Pacific Standard Time", -480),
+    abbreviation("PDT", "Pacific Daylight Time", -420),
+    abbreviation("MST", "Mountain Standard Time", -420),
+    abbreviation("MDT", "Mountain Daylight Time", -360),
+    abbreviation("CST", "Central Standard Time (North America)", -360),
+    abbreviation("CST", "China Standard Time", 480),
+    abbreviation("CST", "Cuba Standard Time", -300),
+    abbreviation("CDT", "Central Daylight Time", -300),
+    abbreviation("EST", "Eastern Standard Time", -300),
+    abbreviation("EDT", "Eastern Daylight Time", -240),
+    abbreviation("BRT", "Brasília Time", -180),
+    abbreviation("WET", "Western European Time", 0),
+    abbreviation("WEST", "Western European Summer Time", 60),
+    abbreviation("BST", "British Summer Time", 60),
+    abbreviation("IST", "Irish Standard Time", 60),
+    abbreviation("CET", "Central European Time", 60),
+    abbreviation("CEST", "Central European Summer Time", 120),
+    abbreviation("EET", "Eastern European Time", 120),
+    abbreviation("EEST", "Eastern European Summer Time", 180),
+    abbreviation("IST", "Israel Standard Time", 120),
+    abbreviation("MSK", "Moscow Time", 180),
+    abbreviation("GST", "Gulf Standard Time", 240),
+    abbreviation("IST", "India Standard Time", 330),
+    abbreviation("SGT", "Singapore Time", 480),
+    abbreviation("HKT", "Hong Kong Time", 480),
+    abbreviation("AWST", "Australian Western Standard Time", 480),
+    abbreviation("JST", "Japan Standard Time", 540),
+    abbreviation("KST", "Korea Standard Time", 540),
+    abbreviation("ACST", "Australian Central Standard Time", 570),
+    abbreviation("AEST", "Australian Eastern Standard Time", 600),
+    abbreviation("AEDT", "Australian Eastern Daylight Time", 660),
+    abbreviation("NZST", "New Zealand Standard Time", 720),
+];
+
+// Names for a region's time whatever the time of year, such as the ET in "3pm ET"
+const REGION_ZONES: [(&str, Tz); 5] = [
+    ("ET", Tz::America__New_York),
+    ("CT", Tz::America__Chicago),
+    ("MT", Tz::America__Denver),
+    ("PT", Tz::America__Los_Angeles),
+    ("AKT", Tz::America__Anchorage),
+];
+
+/// Everything `text` can stand for as a timezone abbreviation, ignoring case. Empty if it is not
+/// a known abbreviation, more than one for the likes of IST and CST.
+pub fn abbreviation_meanings(text: &str) -> Vec<ZoneAbbreviation> {
+    ZONE_ABBREVIATIONS.iter()
+        .filter(|zone_abbreviation| zone_abbreviation.abbreviation.eq_ignore_ascii_case(text))
+        .copied()
+        .collect()
+}
+
+/// Day given in a time entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryDate {
+    On(NaiveDate),
+    /// Days after the day already selected, `tomorrow` is 1.
+    DaysAfter(i64),
+    /// The next day falling on this weekday, the day already selected if it does.
+    Next(Weekday),
+}
+
+/// Timezone given in a time entry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntryZone {
+    Named(Tz),
+    /// A fixed offset from UTC in minutes, such as `+05:30` or `UTC-3`.
+    Offset(i32),
+    /// An abbreviation such as `PST`, which may stand for more than one offset.
+    Abbreviation(String),
+}
+
+/// A time typed or pasted in free form, such as `3pm tomorrow`, `9:00 PST` or `+2h`, before it
+/// is pinned to an instant with [`TimeEntry::instant`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeEntry {
+    pub date: Option<EntryDate>,
+    pub time: Option<NaiveTime>,
+    pub zone: Option<EntryZone>,
+    /// Minutes to move on from the time given, or from the time selected when none is.
+    pub shift_minutes: i64,
+}
+
+impl TimeEntry {
+    /// The instant this entry stands for, in the timezone of `selected`.
+    ///
+    /// A time with no timezone is in the timezone of `selected` and a time with no day is on the
+    /// day of `selected` in the timezone given. A shift on its own moves `selected` along.
+    pub fn instant(&self, selected: DateTime<Tz>) -> Result<DateTime<Tz>, String> {
+        let this_tz = selected.timezone();
+        let shift = Duration::minutes(self.shift_minutes);
+        if self.date.is_none() && self.time.is_none() {
+            return Ok(selected + shift);
+        }
+
+        let zone_offset = match self.zone.as_ref() {
+            Some(EntryZone::Abbreviation(text)) => {
+                let meanings = abbreviation_meanings(text);
+                match meanings.as_slice() {
+                    [meaning] => Some(meaning.utc_offset_minutes),
+                    _ => {
+                        let names: Vec<&str> = meanings.iter().map(|meaning| meaning.name).collect();
+                        return Err(format!("{} could be {}, use a timezone name instead", text.to_uppercase(), names.join(" or ")));
+                    },
+                }
+            },
+            Some(EntryZone::Offset(minutes)) => Some(*minutes),
+            _ => None,
+        };
+        let entry_tz = match self.zone.as_ref() {
+            Some(EntryZone::Named(tz)) => *tz,
+            _ => this_tz,
+        };
+
+        // The day and time selected, as seen in the timezone given
+        let selected_local = match zone_offset {
+            Some(minutes) => selected.naive_utc() + Duration::minutes(minutes as i64),
+            None => selected.with_timezone(&entry_tz).naive_local(),
+        };
+        let date = match self.date {
+            Some(EntryDate::On(date)) => date,
+            Some(EntryDate::DaysAfter(days)) => selected_local.date() + Duration::days(days),
+            Some(EntryDate::Next(weekday)) => {
+                let days = (7 + weekday.num_days_from_monday() - selected_local.weekday().num_days_from_monday()) % 7;
+                selected_local.date() + Duration::days(days as i64)
+            },
+            None => selected_local.date(),
+        };
+        let local = date.and_time(self.time.unwrap_or_else(|| selected_local.time()));
+
+        let instant = match zone_offset {
+            Some(minutes) => Utc.from_utc_datetime(&(local - Duration::minutes(minutes as i64))),
+            None => resolve_local(entry_tz, local).0.with_timezone(&Utc),
+        };
+        Ok(instant.with_timezone(&this_tz) + shift)
+    }
+}
+
+/// Reads a time typed or pasted in free form.
+///
+/// Understands times such as `9am`, `9:30 pm`, `14:30`, `noon` and `midnight`, days such as
+/// `today`, `tomorrow`, `Tue` and `2026-11-03`, timezones given by name (`Europe/London`),
+/// abbreviation (`PST`, `ET`) or offset (`+05:30`, `UTC-3`) and shifts such as `+2h` or
+/// `-1h30m`, in any order.
+pub fn parse_time_entry(text: &str) -> Result<TimeEntry, String> {
+    let tokens = entry_tokens(text);
+    if tokens.is_empty() {
+        return Err("enter a time such as 9am, 14:30 Europe/London or +2h".to_string());
+    }
+
+    let mut entry = TimeEntry::default();
+    for token in tokens.iter() {
+        let lower = token.to_lowercase();
+        let repeated = if let Some(date) = parse_day(&lower) {
+            entry.date.replace(date).map(|_| "day")
+        } else if let Some(time) = parse_clock(&lower) {
+            entry.time.replace(time).map(|_| "time")
+        } else if let Some(minutes) = parse_shift(&lower) {
+            entry.shift_minutes += minutes;
+            None
+        } else if let Some(zone) = parse_zone(token) {
+            entry.zone.replace(zone).map(|_| "timezone")
+        } else {
+            return Err(format!("'{}' is not a time, day or timezone", token));
+        };
+        if let Some(repeated) = repeated {
+            return Err(format!("more than one {} in '{}'", repeated, text.trim()));
+        }
+    }
+
+    Ok(entry)
+}
+
+/// Picks the first time, and the first day and timezone, out of a message such as "Can we meet
+/// Tue 3pm ET?", skipping any other words. Unlike [`parse_time_entry`] a time must be given and
+/// shifts are not looked for.
+///
+/// As a message is mostly ordinary words, short weekday names such as `Sun` only count next to
+/// the time and timezones have to be upper case abbreviations, full names such as
+/// `Europe/London` or offsets with their minutes or a `UTC` in front.
+pub fn find_time_entry(text: &str) -> Result<TimeEntry, String> {
+    let tokens = entry_tokens(text);
+    let lowers: Vec<String> = tokens.iter().map(|token| token.to_lowercase()).collect();
+    let time_index = lowers.iter().position(|lower| parse_clock(lower).is_some())
+        .ok_or_else(|| "no time such as 3pm or 15:00 found".to_string())?;
+
+    let mut entry = TimeEntry { time: parse_clock(&lowers[time_index]), ..TimeEntry::default() };
+    for (index, (token, lower)) in tokens.iter().zip(lowers.iter()).enumerate() {
+        let next_to_time = index + 1 == time_index || index == time_index + 1;
+        if let Some(date) = parse_message_day(lower, next_to_time) {
+            entry.date.get_or_insert(date);
+        } else if let Some(zone) = parse_message_zone(token) {
+            entry.zone.get_or_insert(zone);
+        }
+    }
+    Ok(entry)
+}
+
+const MERIDIEMS: [&str; 4] = ["am", "pm", "a.m.", "p.m."];
+
+// Words of the entry, with am or pm joined to the time before it and ISO 8601 date-times such
+// as 2026-11-03T16:00Z split into date, time and offset
+fn entry_tokens(text: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = vec![];
+    for word in text.split_whitespace() {
+        let word = trim_punctuation(word);
+        if MERIDIEMS.contains(&word.to_lowercase().as_str()) && !tokens.is_empty() {
+            tokens.last_mut().unwrap().push_str(word);
+            continue;
+        }
+
+        match word.find(['T', 't']) {
+            Some(10) if parse_date(&word[..10]).is_some() => {
+                tokens.push(word[..10].to_string());
+                let rest = &word[11..];
+                match rest.find(['Z', 'z', '+', '-']) {
+                    Some(index) => {
+                        tokens.push(rest[..index].to_string());
+                        tokens.push(rest[index..].to_string());
+                    },
+                    None => tokens.push(rest.to_string()),
+                }
+            },
+            _ => tokens.push(word.to_string()),
+        }
+    }
+    tokens.retain(|token| !token.is_empty());
+    tokens
+}
+
+// Quotes, brackets and punctuation around a word, keeping the full stop of a.m. and p.m.
+fn trim_punctuation(word: &str) -> &str {
+    let word = word.trim_start_matches(['(', '[', '"', '\'', '\u{201c}'])
+        .trim_end_matches([',', ';', '!', '?', ')', ']', '"', '\'', '\u{201d}']);
+    let lower = word.to_lowercase();
+    if word.ends_with('.') && !lower.ends_with("a.m.") && !lower.ends_with("p.m.") {
+        return &word[..word.len() - 1];
+    }
+    word
+}
+
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(text, "%Y/%m/%d"))
+        .ok()
+}
+
+fn parse_day(lower: &str) -> Option<EntryDate> {
+    match lower {
+        "today" => Some(EntryDate::DaysAfter(0)),
+        "tomorrow" => Some(EntryDate::DaysAfter(1)),
+        "yesterday" => Some(EntryDate::DaysAfter(-1)),
+        "tues" => Some(EntryDate::Next(Weekday::Tue)),
+        "weds" => Some(EntryDate::Next(Weekday::Wed)),
+        "thur" | "thurs" => Some(EntryDate::Next(Weekday::Thu)),
+        _ => parse_date(lower).map(EntryDate::On)
+            .or_else(|| lower.parse().ok().map(EntryDate::Next)),
+    }
+}
+
+// A day in a message. Full weekday names end in "day", short ones are ordinary words as well
+// ("sun", "sat") so are only taken next to the time
+fn parse_message_day(lower: &str, next_to_time: bool) -> Option<EntryDate> {
+    match parse_day(lower)? {
+        EntryDate::Next(_) if !next_to_time && !lower.ends_with("day") => None,
+        day => Some(day),
+    }
+}
+
+// 9am, 9:30pm, 14:30, 14:30:00, noon or midnight. A bare hour needs am or pm
+fn parse_clock(lower: &str) -> Option<NaiveTime> {
+    match lower {
+        "noon" | "midday" => return Some(NaiveTime::from_hms_opt(12, 0, 0).unwrap()),
+        "midnight" => return Some(NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+        _ => (),
+    }
+
+    let (clock, pm) = match MERIDIEMS.iter().find(|meridiem| lower.ends_with(*meridiem)) {
+        Some(meridiem) => (&lower[..lower.len() - meridiem.len()], Some(meridiem.starts_with('p'))),
+        None => (lower, None),
+    };
+    let mut parts = clock.split(':');
+    let hour: u32 = parse_digits(parts.next()?, 1..=2)?;
+    let minute: u32 = match parts.next() {
+        Some(minute) => parse_digits(minute, 2..=2)?,
+        None if pm.is_some() => 0,
+        None => return None,
+    };
+    let second: u32 = match parts.next() {
+        Some(second) => parse_digits(second, 2..=2)?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let hour = match pm {
+        Some(_) if hour == 0 || hour > 12 => return None,
+        Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, second)
+}
+
+fn parse_digits(text: &str, lengths: std::ops::RangeInclusive<usize>) -> Option<u32> {
+    if !lengths.contains(&text.len()) || !text.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+// +2h, -30m, +1h30m or +1d
+fn parse_shift(lower: &str) -> Option<i64> {
+    let sign = match lower.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+
+    let mut minutes = 0;
+    let mut number = String::new();
+    for c in lower[1..].chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit_minutes = match c {
+            'w' => 7 * 24 * 60,
+            'd' => 24 * 60,
+            'h' => 60,
+            'm' => 1,
+            _ => return None,
+        };
+        minutes += number.parse::<i64>().ok()? * unit_minutes;
+        number.clear();
+    }
+    // A trailing number with no unit is more likely an offset such as +0530
+    if !number.is_empty() || (minutes == 0 && !lower.ends_with(|c: char| c.is_alphabetic())) {
+        return None;
+    }
+    Some(sign * minutes)
+}
+
+fn parse_zone(token: &str) -> Option<EntryZone> {
+    if let Ok(tz) = Tz::from_str(token) {
+        return Some(EntryZone::Named(tz));
+    }
+    if let Some(tz) = TZ_VARIANTS.iter().find(|tz| tz.name().eq_ignore_ascii_case(token)) {
+        return Some(EntryZone::Named(*tz));
+    }
+    if let Some((_, tz)) = REGION_ZONES.iter().find(|(name, _)| name.eq_ignore_ascii_case(token)) {
+        return Some(EntryZone::Named(*tz));
+    }
+    if !abbreviation_meanings(token).is_empty() {
+        return Some(EntryZone::Abbreviation(token.to_uppercase()));
+    }
+    parse_offset(&token.to_uppercase(), true)
+}
+
+// A timezone in a message, where "et", "z" or "Japan" are more likely to be ordinary words
+fn parse_message_zone(token: &str) -> Option<EntryZone> {
+    if token.contains('/') {
+        return Tz::from_str(token).ok().map(EntryZone::Named);
+    }
+    if token.chars().all(|c| c.is_ascii_uppercase()) {
+        if let Some((_, tz)) = REGION_ZONES.iter().find(|(name, _)| *name == token) {
+            return Some(EntryZone::Named(*tz));
+        }
+        if !abbreviation_meanings(token).is_empty() {
+            return Some(EntryZone::Abbreviation(token.to_string()));
+        }
+        return None;
+    }
+    // A bare +1 is more likely agreement than an offset
+    parse_offset(token, token.starts_with("UTC") || token.starts_with("GMT"))
+}
+
+// +05:30, -0300 or UTC+2 in upper case, hours on their own only with `bare_hours`
+fn parse_offset(upper: &str, bare_hours: bool) -> Option<EntryZone> {
+    let offset = upper.strip_prefix("UTC").or_else(|| upper.strip_prefix("GMT")).unwrap_or(upper);
+    let sign = match offset.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = match offset[1..].split_once(':') {
+        Some((hours, minutes)) => (parse_digits(hours, 1..=2)?, parse_digits(minutes, 2..=2)?),
+        // Only sliced by byte once it is known to be all ASCII
+        None if offset.len() == 5 && offset.is_ascii() => (parse_digits(&offset[1..3], 2..=2)?, parse_digits(&offset[3..], 2..=2)?),
+        None if bare_hours => (parse_digits(&offset[1..], 1..=2)?, 0),
+        None => return None,
+    };
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    Some(EntryZone::Offset(sign * (hours * 60 + minutes) as i32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+    use chrono_tz::{Australia, Europe};
+
+    fn entry_instant(text: &str) -> Result<DateTime<Tz>, String> {
+        // 10:20 on a Wednesday in Canberra
+        let selected = Australia::Canberra.with_ymd_and_hms(2026, 11, 4, 10, 20, 0).unwrap();
+        parse_time_entry(text)?.instant(selected)
+    }
+
+    fn local(text: &str) -> String {
+        entry_instant(text).unwrap().format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    #[test]
+    fn times_of_day() {
+        assert_eq!(local("9am"), "2026-11-04 09:00");
+        assert_eq!(local("9:30 PM"), "2026-11-04 21:30");
+        assert_eq!(local("12am"), "2026-11-04 00:00");
+        assert_eq!(local("14:30"), "2026-11-04 14:30");
+        assert_eq!(local("noon"), "2026-11-04 12:00");
+        assert_eq!(local("3pm tomorrow"), "2026-11-05 15:00");
+        assert!(parse_time_entry("9").is_err());
+        assert!(parse_time_entry("25:00").is_err());
+        assert!(parse_time_entry("13pm").is_err());
+        assert!(parse_time_entry("9:00 +0é0").is_err());
+    }
+
+    #[test]
+    fn times_in_other_zones() {
+        // PST is a fixed UTC-8, 9:00 PST is 04:00 the next morning in Canberra
+        assert_eq!(local("9:00 PST"), "2026-11-04 04:00");
+        assert_eq!(local("2026-11-03 16:00 Europe/London"), "2026-11-04 03:00");
+        assert_eq!(local("2026-11-03T16:00Z"), "2026-11-04 03:00");
+        assert_eq!(local("16:00 +05:30"), "2026-11-04 21:30");
+        assert_eq!(local("16:00 europe/london"), local("16:00 Europe/London"));
+        assert_eq!(parse_time_entry("9:00 UTC-3").unwrap().zone, Some(EntryZone::Offset(-180)));
+
+        let err = entry_instant("9:00 IST").unwrap_err();
+        assert!(err.contains("India Standard Time"), "{}", err);
+        let mut entry = parse_time_entry("9:00 IST").unwrap();
+        entry.zone = Some(EntryZone::Offset(abbreviation_meanings("ist")[2].utc_offset_minutes));
+        assert_eq!(entry.instant(Europe::London.with_ymd_and_hms(2026, 11, 4, 8, 0, 0).unwrap()).unwrap().hour(), 3);
+    }
+
+    #[test]
+    fn times_found_in_messages() {
+        let local_found = |text: &str| find_time_entry(text).unwrap()
+            .instant(Australia::Canberra.with_ymd_and_hms(2026, 11, 4, 10, 20, 0).unwrap()).unwrap()
+            .format("%a %Y-%m-%d %H:%M").to_string();
+
+        // The Wednesday in Canberra is still Tuesday in New York, so this Tuesday is meant
+        assert_eq!(local_found("Can we meet Tue 3pm ET?"), "Wed 2026-11-04 07:00");
+        assert_eq!(local_found("(Thursday, 9 a.m. PT)"), "Fri 2026-11-06 04:00");
+        assert_eq!(local_found("standup moved to 2026-11-09 at 16:30 Europe/London."), "Tue 2026-11-10 03:30");
+        assert!(find_time_entry("see you on Tuesday").is_err());
+
+        let entry = find_time_entry("call at 11am IST").unwrap();
+        assert_eq!(entry.zone, Some(EntryZone::Abbreviation("IST".to_string())));
+        assert_eq!(abbreviation_meanings("cst").len(), 3);
+        assert_eq!(find_time_entry("Sun. 3pm UTC+2").unwrap().date, Some(EntryDate::Next(Weekday::Sun)));
+        assert_eq!(find_time_entry("Sun. 3pm UTC+2").unwrap().zone, Some(EntryZone::Offset(120)));
+        assert!(find_time_entry("meet at 3pm +1é2").is_ok());
+    }
+
+    #[test]
+    fn ordinary_words_in_messages_are_skipped() {
+        let found = |text: &str| find_time_entry(text).unwrap();
+
+        assert_eq!(found("sun is out at 3pm"), TimeEntry { time: Some(NaiveTime::from_hms_opt(15, 0, 0).unwrap()), ..TimeEntry::default() });
+        assert_eq!(found("at 3pm et al").zone, None);
+        assert_eq!(found("sat down at 9am, z it is").date, None);
+        assert_eq!(found("sat down at 9am, z it is").zone, None);
+        assert_eq!(found("3pm pt 2 of the plan").zone, None);
+        assert_eq!(found("Japan call at 3pm").zone, None);
+        assert_eq!(found("+1 for 3pm").zone, None);
+        assert_eq!(found("3pm on Sunday").date, Some(EntryDate::Next(Weekday::Sun)));
+        assert_eq!(found("3pm Asia/Tokyo").zone, Some(EntryZone::Named(Tz::Asia__Tokyo)));
+    }
+
+    #[test]
+    fn shifts_move_the_selected_time() {
+        assert_eq!(local("+2h"), "2026-11-04 12:20");
+        assert_eq!(local("-1h30m"), "2026-11-04 08:50");
+        assert_eq!(local("9am +1d"), "2026-11-05 09:00");
+        assert!(parse_time_entry("+2x").is_err());
+        assert!(parse_time_entry("").is_err());
+        assert!(parse_time_entry("9am 10am").is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::{MINUTES_PER_DAY, format_minutes, utc_offset_minutes};
+
+/// A change of UTC offset in one timezone, with the offsets either side of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffsetTransition {
+    /// First instant with the new offset.
+    pub at: DateTime<Tz>,
+    /// UTC offset in minutes before the change.
+    pub from_minutes: i32,
+    /// UTC offset in minutes from the change on.
+    pub to_minutes: i32,
+}
+
+/// Changes of UTC offset in `tz` from `from` up to `to`, in time order. Assumes a timezone
+/// changes its offset at most once a day and that `from` is on a whole minute.
+pub fn offset_transitions(tz: Tz, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<OffsetTransition> {
+    let offset_at = |minutes: i64| utc_offset_minutes(&(from + Duration::minutes(minutes)).with_timezone(&tz));
+    let total_minutes = (to - from).num_minutes();
+    let mut transitions = vec![];
+
+    // A day at a time, then narrowed down to the minute
+    let mut prev = 0;
+    while prev < total_minutes {
+        let next = std::cmp::min(prev + MINUTES_PER_DAY as i64, total_minutes);
+        if offset_at(next) != offset_at(prev) {
+            let (mut before, mut after) = (prev, next);
+            while after - before > 1 {
+                let mid = (before + after) / 2;
+                if offset_at(mid) == offset_at(before) {
+                    before = mid;
+                } else {
+                    after = mid;
+                }
+            }
+            transitions.push(OffsetTransition {
+                at: (from + Duration::minutes(after)).with_timezone(&tz),
+                from_minutes: offset_at(before),
+                to_minutes: offset_at(after),
+            });
+        }
+        prev = next;
+    }
+
+    transitions
+}
+
+/// A change of UTC offset coming up in a row's timezone or in the base timezone, with the gap
+/// between the two either side of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffsetWarning {
+    pub transition: OffsetTransition,
+    /// The change is in the base timezone rather than the row's own.
+    pub in_base: bool,
+    /// Minutes between this timezone and the base timezone, ignoring which is ahead.
+    pub gap_before_minutes: i32,
+    pub gap_after_minutes: i32,
+}
+
+impl OffsetWarning {
+    /// Such as `US/Pacific shifts -1h on 2026-11-01; gap with base changes from 18h to 19h`,
+    /// with `(base)` after a change in the base timezone. The base timezone's own row has no gap
+    /// to give and points at the other rows instead.
+    pub fn describe(&self, is_base: bool) -> String {
+        let transition = &self.transition;
+        let mut text = format!("{}{} shifts {} on {}", transition.at.timezone().name(), if self.in_base { " (base)" } else { "" },
+            format_minutes(transition.to_minutes - transition.from_minutes), transition.at.format("%Y-%m-%d"));
+        if is_base {
+            text.push_str("; other rows warn if their gap with base changes");
+        } else if self.gap_before_minutes != self.gap_after_minutes {
+            // The sign of format_minutes means nothing for a gap
+            text.push_str(&format!("; gap with base changes from {} to {}",
+                &format_minutes(self.gap_before_minutes)[1..], &format_minutes(self.gap_after_minutes)[1..]));
+        }
+        text
+    }
+}
+
+/// Changes of UTC offset over the `weeks` weeks from `from` that move `this_tz` against the base
+/// timezone, for warning about meetings that will move. These are the changes in `this_tz` itself
+/// along with those in `base_tz` that alter the gap, in time order.
+pub fn upcoming_offset_warnings(this_tz: Tz, base_tz: Tz, from: DateTime<Utc>, weeks: u32) -> Vec<OffsetWarning> {
+    // offset_transitions works in whole minutes
+    let from = Utc.timestamp_opt(from.timestamp() / 60 * 60, 0).unwrap();
+    let to = from + Duration::weeks(weeks as i64);
+    let gap_at = |instant: DateTime<Utc>| {
+        (utc_offset_minutes(&instant.with_timezone(&this_tz)) - utc_offset_minutes(&instant.with_timezone(&base_tz))).abs()
+    };
+    let warning = |transition: OffsetTransition, in_base| {
+        let at = transition.at.with_timezone(&Utc);
+        OffsetWarning {
+            gap_before_minutes: gap_at(at - Duration::minutes(1)),
+            gap_after_minutes: gap_at(at),
+            in_base,
+            transition,
+        }
+    };
+
+    let mut warnings: Vec<OffsetWarning> = offset_transitions(this_tz, from, to).into_iter()
+        .map(|transition| warning(transition, false))
+        .collect();
+    if this_tz != base_tz {
+        // A change in the base timezone at the same instant is already covered by the row's own
+        let base_warnings: Vec<OffsetWarning> = offset_transitions(base_tz, from, to).into_iter()
+            .map(|transition| warning(transition, true))
+            .filter(|base_warning| base_warning.gap_before_minutes != base_warning.gap_after_minutes
+                && !warnings.iter().any(|warning| warning.transition.at == base_warning.transition.at))
+            .collect();
+        warnings.extend(base_warnings);
+        warnings.sort_by_key(|warning| warning.transition.at);
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{Australia, Europe, US};
+
+    #[test]
+    fn offset_warnings_give_the_new_gap() {
+        let from = Utc.with_ymd_and_hms(2026, 10, 18, 12, 30, 15).unwrap();
+
+        let warnings = upcoming_offset_warnings(US::Pacific, Australia::Sydney, from, 4);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].describe(false), "US/Pacific shifts -1h on 2026-11-01; gap with base changes from 18h to 19h");
+
+        assert!(upcoming_offset_warnings(US::Pacific, Australia::Sydney, from, 1).is_empty());
+    }
+
+    #[test]
+    fn base_changes_warn_on_every_row() {
+        let from = Utc.with_ymd_and_hms(2026, 10, 18, 12, 30, 15).unwrap();
+
+        let warnings = upcoming_offset_warnings(Europe::London, US::Pacific, from, 4);
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].describe(false), "Europe/London shifts -1h on 2026-10-25; gap with base changes from 8h to 7h");
+        assert_eq!(warnings[1].describe(false), "US/Pacific (base) shifts -1h on 2026-11-01; gap with base changes from 7h to 8h");
+
+        let warnings = upcoming_offset_warnings(US::Pacific, US::Pacific, from, 4);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].describe(true), "US/Pacific shifts -1h on 2026-11-01; other rows warn if their gap with base changes");
+
+        // Berlin and London change together, so the gap stays the same and there is one warning
+        let warnings = upcoming_offset_warnings(Europe::Berlin, Europe::London, from, 4);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].describe(false), "Europe/Berlin shifts -1h on 2026-10-25");
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use serde::{Serialize, Deserialize};
+
+use crate::{ZoneDay, resolve_local};
+
+/// Working hours kept in one timezone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkHours {
+    /// Local time the working day starts.
+    pub start: NaiveTime,
+    /// Local time the working day ends, at or before `start` for a shift running past midnight.
+    pub end: NaiveTime,
+    /// Days of the week worked, by the day a shift starts on.
+    pub weekdays: Vec<Weekday>,
+}
+
+impl Default for WorkHours {
+    fn default() -> Self {
+        Self {
+            start: NaiveTime::from_hms_opt(8, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            weekdays: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+        }
+    }
+}
+
+impl WorkHours {
+    /// Start and end of the shift starting on local `date` in `tz`, whether or not it is a working day.
+    pub fn shift_on(&self, tz: Tz, date: NaiveDate) -> (DateTime<Tz>, DateTime<Tz>) {
+        let end_date = if self.end <= self.start { date.succ_opt().unwrap() } else { date };
+
+        (resolve_local(tz, date.and_time(self.start)).0, resolve_local(tz, end_date.and_time(self.end)).0)
+    }
+
+    /// Whether `instant` is inside working hours in its own timezone.
+    pub fn contains(&self, instant: &DateTime<Tz>) -> bool {
+        let local_date = instant.naive_local().date();
+
+        // A shift running past midnight may have started the day before
+        [local_date.pred_opt().unwrap(), local_date].iter()
+            .filter(|date| self.weekdays.contains(&date.weekday()))
+            .any(|date| {
+                let (start, end) = self.shift_on(instant.timezone(), *date);
+                *instant >= start && *instant < end
+            })
+    }
+
+    /// Parts of the base day inside working hours in the zone of `zone_day`, as `(from, to)`
+    /// fractions of the day in time order.
+    pub fn day_spans(&self, zone_day: &ZoneDay) -> Vec<(f64, f64)> {
+        let this_tz = zone_day.start.timezone();
+        let mut spans = vec![];
+
+        let mut date = zone_day.start.naive_local().date().pred_opt().unwrap();
+        while date <= zone_day.end.naive_local().date() {
+            if self.weekdays.contains(&date.weekday()) {
+                let (start, end) = self.shift_on(this_tz, date);
+                let from = zone_day.offset_for_instant(start).max(0.0);
+                let to = zone_day.offset_for_instant(end).min(1.0);
+                if from < to {
+                    spans.push((from, to));
+                }
+            }
+            date = date.succ_opt().unwrap();
+        }
+
+        spans
+    }
+
+    /// Length of a shift in minutes, ignoring any clock change during it.
+    pub fn minutes(&self) -> i64 {
+        let minutes = (self.end - self.start).num_minutes();
+        if minutes <= 0 {
+            minutes + 24 * 60
+        } else {
+            minutes
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::current_timezone_range;
+    use chrono::TimeZone;
+    use chrono_tz::{Asia, Europe};
+
+    #[test]
+    fn spans_follow_local_hours_and_weekdays() {
+        let germany = WorkHours {
+            start: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(15, 30, 0).unwrap(),
+            ..WorkHours::default()
+        };
+
+        // Friday 2021-01-15 in London is 01:00 Friday to 01:00 Saturday in Berlin
+        let zone_day = current_timezone_range(Europe::London, Europe::Berlin, NaiveDate::from_ymd_opt(2021, 1, 15).unwrap());
+        assert_eq!(germany.day_spans(&zone_day), vec![(6.0 / 24.0, 14.5 / 24.0)]);
+
+        let saturday = current_timezone_range(Europe::London, Europe::Berlin, NaiveDate::from_ymd_opt(2021, 1, 16).unwrap());
+        assert!(germany.day_spans(&saturday).is_empty());
+        assert_eq!(germany.minutes(), 8 * 60 + 30);
+    }
+
+    #[test]
+    fn overnight_shift_wraps_into_next_day() {
+        let night = WorkHours {
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+            weekdays: vec![Weekday::Thu],
+        };
+
+        let friday_early = Asia::Kolkata.with_ymd_and_hms(2021, 1, 15, 3, 0, 0).unwrap();
+        let friday_late = Asia::Kolkata.with_ymd_and_hms(2021, 1, 15, 23, 0, 0).unwrap();
+        assert!(night.contains(&friday_early));
+        assert!(!night.contains(&friday_late));
+        assert_eq!(night.minutes(), 8 * 60);
+    }
+}
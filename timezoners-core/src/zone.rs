@@ -0,0 +1,276 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Serialize, Serializer, Deserialize};
+
+use crate::{WorkHours, utc_offset_minutes};
+
+/// Red, green, blue and alpha, each `0.0` - `1.0`.
+pub type Colour = (f64, f64, f64, f64);
+
+/// A timezone row as the user has set it up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneEntry {
+    pub tz: Tz,
+    /// Shown for the row in place of the timezone name.
+    pub label: Option<String>,
+    /// Whether the initials of the label are shown next to it.
+    pub show_badge: bool,
+    /// Colours for this row only, the app wide colours are used when not set.
+    pub midday_colour: Option<Colour>,
+    pub workday_colour: Option<Colour>,
+    pub work_hours: WorkHours,
+    /// iCalendar file whose events are shown as busy on the row.
+    pub calendar: Option<PathBuf>,
+}
+
+impl ZoneEntry {
+    pub fn new(tz: Tz) -> Self {
+        Self {
+            tz,
+            label: None,
+            show_badge: false,
+            midday_colour: None,
+            workday_colour: None,
+            work_hours: WorkHours::default(),
+            calendar: None,
+        }
+    }
+
+    /// The label, or the timezone name when there is no label.
+    pub fn display_name(&self) -> String {
+        self.label.clone().unwrap_or_else(|| self.tz.name().to_string())
+    }
+
+    /// Initials of the label, see `label_initials`.
+    pub fn initials(&self) -> Option<String> {
+        label_initials(self.label.as_ref()?)
+    }
+}
+
+/// Up to two initials from a label, anything in brackets is left out so "Alice Smith (Berlin)"
+/// gives "AS".
+pub fn label_initials(label: &str) -> Option<String> {
+    let name = label.split('(').next().unwrap_or("");
+    let initials: String = name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect();
+
+    if initials.is_empty() { None } else { Some(initials) }
+}
+
+/// Orders that rows can be sorted into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneSort {
+    /// Furthest behind UTC first.
+    UtcOffset,
+    /// By timezone name.
+    Alphabetical,
+    /// By label, rows without a label after those with one.
+    Label,
+}
+
+/// Compares two entries for `sort`, using the UTC offsets in force at `instant`. Entries that
+/// would otherwise be equal are kept in timezone name order.
+pub fn compare_entries(a: &ZoneEntry, b: &ZoneEntry, sort: ZoneSort, instant: DateTime<Utc>) -> Ordering {
+    let by_name = a.tz.name().cmp(b.tz.name());
+    match sort {
+        ZoneSort::UtcOffset => utc_offset_minutes(&instant.with_timezone(&a.tz))
+            .cmp(&utc_offset_minutes(&instant.with_timezone(&b.tz)))
+            .then(by_name),
+        ZoneSort::Alphabetical => by_name,
+        ZoneSort::Label => match (&a.label, &b.label) {
+            (Some(a_label), Some(b_label)) => a_label.to_lowercase().cmp(&b_label.to_lowercase()).then(by_name),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => by_name,
+        },
+    }
+}
+
+/// A `ZoneEntry` as saved. The timezone is kept by name so that one bad name in a config file
+/// does not stop the other entries loading. Both forms are read but always written as an entry,
+/// as toml cannot mix plain names and tables in one array.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum SavedZone {
+    /// Timezone name only, as saved before entries had their own settings.
+    Name(String),
+    Entry {
+        zone: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        #[serde(default)]
+        show_badge: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        midday_colour: Option<Colour>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        workday_colour: Option<Colour>,
+        #[serde(default)]
+        work_hours: WorkHours,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        calendar: Option<PathBuf>,
+    },
+}
+
+// The fields of `SavedZone::Entry` for writing either form
+#[derive(Serialize)]
+struct SavedEntry<'a> {
+    zone: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+    show_badge: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    midday_colour: Option<Colour>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workday_colour: Option<Colour>,
+    work_hours: Cow<'a, WorkHours>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    calendar: Option<&'a PathBuf>,
+}
+
+impl Serialize for SavedZone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let saved_entry = match self {
+            SavedZone::Name(zone) => SavedEntry {
+                zone,
+                label: None,
+                show_badge: false,
+                midday_colour: None,
+                workday_colour: None,
+                work_hours: Cow::Owned(WorkHours::default()),
+                calendar: None,
+            },
+            SavedZone::Entry { zone, label, show_badge, midday_colour, workday_colour, work_hours, calendar } => SavedEntry {
+                zone,
+                label: label.as_deref(),
+                show_badge: *show_badge,
+                midday_colour: *midday_colour,
+                workday_colour: *workday_colour,
+                work_hours: Cow::Borrowed(work_hours),
+                calendar: calendar.as_ref(),
+            },
+        };
+        saved_entry.serialize(serializer)
+    }
+}
+
+impl SavedZone {
+    /// Timezone name as it was saved.
+    pub fn zone_name(&self) -> &str {
+        match self {
+            SavedZone::Name(zone) => zone,
+            SavedZone::Entry { zone, .. } => zone,
+        }
+    }
+
+    /// The entry saved, or a description of the problem when the timezone name is not known.
+    pub fn to_entry(&self) -> Result<ZoneEntry, String> {
+        let tz: Tz = self.zone_name().trim().parse()
+            .map_err(|_| format!("Unknown timezone \"{}\"", self.zone_name()))?;
+
+        Ok(match self {
+            SavedZone::Name(_) => ZoneEntry::new(tz),
+            SavedZone::Entry { label, show_badge, midday_colour, workday_colour, work_hours, calendar, .. } => ZoneEntry {
+                tz,
+                label: label.clone(),
+                show_badge: *show_badge,
+                midday_colour: *midday_colour,
+                workday_colour: *workday_colour,
+                work_hours: work_hours.clone(),
+                calendar: calendar.clone(),
+            },
+        })
+    }
+}
+
+impl From<&ZoneEntry> for SavedZone {
+    fn from(entry: &ZoneEntry) -> Self {
+        SavedZone::Entry {
+            zone: entry.tz.name().to_string(),
+            label: entry.label.clone(),
+            show_badge: entry.show_badge,
+            midday_colour: entry.midday_colour,
+            workday_colour: entry.workday_colour,
+            work_hours: entry.work_hours.clone(),
+            calendar: entry.calendar.clone(),
+        }
+    }
+}
+
+/// Reads saved entries in order, returning those that could be read along with a warning for
+/// each one that could not. Blank names are left out without a warning.
+pub fn load_zones(saved: &[SavedZone]) -> (Vec<ZoneEntry>, Vec<String>) {
+    let mut entries = vec![];
+    let mut warnings = vec![];
+
+    for saved_zone in saved.iter().filter(|saved_zone| !saved_zone.zone_name().trim().is_empty()) {
+        match saved_zone.to_entry() {
+            Ok(entry) => entries.push(entry),
+            Err(warning) => warnings.push(warning),
+        }
+    }
+
+    (entries, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::{Australia, Europe};
+
+    #[test]
+    fn bad_names_are_reported_not_loaded() {
+        let saved = vec![
+            SavedZone::Name("Australia/Canberra".to_string()),
+            SavedZone::Name("".to_string()),
+            SavedZone::Name("Mars/Olympus_Mons".to_string()),
+            SavedZone::from(&ZoneEntry { label: Some("Alice".to_string()), ..ZoneEntry::new(Europe::Berlin) }),
+        ];
+
+        let (entries, warnings) = load_zones(&saved);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], ZoneEntry::new(Australia::Canberra));
+        assert_eq!(entries[1].tz, Europe::Berlin);
+        assert_eq!(entries[1].label.as_deref(), Some("Alice"));
+        assert_eq!(warnings, vec!["Unknown timezone \"Mars/Olympus_Mons\"".to_string()]);
+    }
+
+    #[test]
+    fn sorts_by_offset_name_and_label() {
+        use chrono::TimeZone;
+        use chrono_tz::{America, Asia};
+
+        let mut entries = vec![
+            ZoneEntry { label: Some("bob".to_string()), ..ZoneEntry::new(Asia::Kolkata) },
+            ZoneEntry::new(America::New_York),
+            ZoneEntry { label: Some("Alice".to_string()), ..ZoneEntry::new(Europe::Berlin) },
+        ];
+        let instant = Utc.with_ymd_and_hms(2021, 1, 14, 12, 0, 0).unwrap();
+        let names = |entries: &[ZoneEntry]| entries.iter().map(|entry| entry.tz.name()).collect::<Vec<_>>();
+
+        entries.sort_by(|a, b| compare_entries(a, b, ZoneSort::UtcOffset, instant));
+        assert_eq!(names(&entries), vec!["America/New_York", "Europe/Berlin", "Asia/Kolkata"]);
+        entries.sort_by(|a, b| compare_entries(a, b, ZoneSort::Alphabetical, instant));
+        assert_eq!(names(&entries), vec!["America/New_York", "Asia/Kolkata", "Europe/Berlin"]);
+        entries.sort_by(|a, b| compare_entries(a, b, ZoneSort::Label, instant));
+        assert_eq!(names(&entries), vec!["Europe/Berlin", "Asia/Kolkata", "America/New_York"]);
+    }
+
+    #[test]
+    fn initials_leave_out_brackets() {
+        let mut entry = ZoneEntry::new(Europe::Berlin);
+        assert_eq!(entry.initials(), None);
+        assert_eq!(entry.display_name(), "Europe/Berlin");
+
+        entry.label = Some("alice smith (Berlin)".to_string());
+        assert_eq!(entry.initials().as_deref(), Some("AS"));
+        entry.label = Some("(Berlin office)".to_string());
+        assert_eq!(entry.initials(), None);
+    }
+}
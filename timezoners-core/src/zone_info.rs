@@ -0,0 +1,77 @@
+use chrono::DateTime;
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+
+use crate::{format_minutes, utc_offset_minutes};
+
+/// How a timezone stands at one instant, compared with the base timezone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneInfo {
+    /// Such as `AEDT` or `PST`, some timezones only have a number such as `+0545`.
+    pub abbreviation: String,
+    pub utc_offset_minutes: i32,
+    /// Minutes this timezone is ahead of the base timezone, negative when it is behind.
+    pub base_difference_minutes: i32,
+    /// Daylight saving time is in effect.
+    pub dst: bool,
+}
+
+impl ZoneInfo {
+    /// `instant` in its own timezone compared with `base_tz` at the same instant.
+    pub fn at(instant: DateTime<Tz>, base_tz: Tz) -> Self {
+        let base_offset_minutes = utc_offset_minutes(&instant.with_timezone(&base_tz));
+        let utc_offset_minutes = utc_offset_minutes(&instant);
+        Self {
+            abbreviation: instant.offset().abbreviation().to_string(),
+            utc_offset_minutes,
+            base_difference_minutes: utc_offset_minutes - base_offset_minutes,
+            dst: !instant.offset().dst_offset().is_zero(),
+        }
+    }
+
+    /// One line such as `AEDT  UTC+11:00  +19h  DST`, leaving out the difference for the base
+    /// timezone itself.
+    pub fn summary(&self, is_base: bool) -> String {
+        let mut parts = vec![self.abbreviation.clone(), format_utc_offset(self.utc_offset_minutes)];
+        if !is_base {
+            parts.push(match self.base_difference_minutes {
+                0 => "same as base".to_string(),
+                minutes => format_minutes(minutes),
+            });
+        }
+        if self.dst {
+            parts.push("DST".to_string());
+        }
+        parts.join("  ")
+    }
+}
+
+/// Formats a UTC offset in minutes such as `UTC+11:00` or `UTC-3:30`.
+pub fn format_utc_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    format!("UTC{}{}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::{Asia, Australia, US};
+
+    #[test]
+    fn zone_info_against_base() {
+        let instant = Australia::Sydney.with_ymd_and_hms(2021, 1, 14, 9, 0, 0).unwrap();
+
+        let sydney = ZoneInfo::at(instant, US::Pacific);
+        assert_eq!(sydney.abbreviation, "AEDT");
+        assert_eq!(sydney.utc_offset_minutes, 11 * 60);
+        assert_eq!(sydney.base_difference_minutes, 19 * 60);
+        assert!(sydney.dst);
+        assert_eq!(sydney.summary(false), "AEDT  UTC+11:00  +19h  DST");
+
+        let pacific = ZoneInfo::at(instant.with_timezone(&US::Pacific), US::Pacific);
+        assert_eq!(pacific.summary(true), "PST  UTC-8:00");
+
+        let kolkata = ZoneInfo::at(instant.with_timezone(&Asia::Kolkata), Australia::Sydney);
+        assert_eq!(kolkata.summary(false), "IST  UTC+5:30  -5h30m");
+    }
+}
@@ -0,0 +1,56 @@
+use serde::{Serialize, Deserialize};
+
+use crate::zone::{SavedZone, ZoneEntry, load_zones};
+
+/// Name given to the set holding the zones saved before there were sets.
+pub const DEFAULT_SET_NAME: &str = "Default";
+
+/// A named list of timezone rows, such as "APAC on-call" or "family".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ZoneSet {
+    pub name: String,
+    #[serde(default)]
+    pub zones: Vec<SavedZone>,
+}
+
+impl ZoneSet {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            zones: vec![],
+        }
+    }
+
+    /// The entries of the set that could be read, see `load_zones`.
+    pub fn entries(&self) -> (Vec<ZoneEntry>, Vec<String>) {
+        load_zones(&self.zones)
+    }
+}
+
+/// `wanted` with surrounding spaces trimmed, followed by a number if it is one of `taken_names`,
+/// so "Family" becomes "Family 2" and then "Family 3". Names are compared ignoring case.
+pub fn unique_set_name(wanted: &str, taken_names: &[&str]) -> String {
+    let wanted = wanted.trim();
+    let taken = |name: &str| taken_names.iter().any(|taken_name| taken_name.to_lowercase() == name.to_lowercase());
+
+    if !taken(wanted) {
+        return wanted.to_string();
+    }
+    (2..).map(|n| format!("{} {}", wanted, n))
+        .find(|name| !taken(name))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_names_are_made_unique() {
+        let taken = ["Family", "family 2", "EU customers"];
+
+        assert_eq!(unique_set_name("  APAC on-call ", &taken), "APAC on-call");
+        assert_eq!(unique_set_name("Family", &taken), "Family 3");
+        assert_eq!(unique_set_name("EU customers", &taken), "EU customers 2");
+    }
+}